@@ -0,0 +1,157 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::registry::{ContentRegistry, DefKind, DefMap, Definition};
+
+/// One field that differs between two versions of a definition, by its path
+/// in the definition, e.g. `components[0].Weapon.damage`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub path: String,
+    /// `None` if the field was added.
+    pub old: Option<Value>,
+    /// `None` if the field was removed.
+    pub new: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefChange {
+    Added,
+    Removed,
+    Changed(Vec<FieldChange>),
+}
+
+/// A definition that differs between two registries.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DefDiff {
+    pub kind: DefKind,
+    pub id: String,
+    pub change: DefChange,
+}
+
+impl fmt::Display for DefDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.change {
+            DefChange::Added => write!(f, "+ {} '{}'", self.kind, self.id),
+            DefChange::Removed => write!(f, "- {} '{}'", self.kind, self.id),
+            DefChange::Changed(fields) => {
+                let show = |value: &Option<Value>| {
+                    value.as_ref().map_or("(none)".to_owned(), Value::to_string)
+                };
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|field| {
+                        format!(
+                            "{} {} -> {}",
+                            field.path,
+                            show(&field.old),
+                            show(&field.new)
+                        )
+                    })
+                    .collect();
+                write!(f, "~ {} '{}': {}", self.kind, self.id, fields.join("; "))
+            }
+        }
+    }
+}
+
+/// Every definition added, removed or changed from `old` to `new`, by kind
+/// and then id.
+pub fn diff_registries(old: &ContentRegistry, new: &ContentRegistry) -> Vec<DefDiff> {
+    let mut diffs = Vec::new();
+    diff_map(&old.items, &new.items, &mut diffs);
+    diff_map(&old.benches, &new.benches, &mut diffs);
+    diff_map(&old.recipes, &new.recipes, &mut diffs);
+    diff_map(&old.loot_tables, &new.loot_tables, &mut diffs);
+    diff_map(&old.tags, &new.tags, &mut diffs);
+    diff_map(&old.statuses, &new.statuses, &mut diffs);
+    diff_map(&old.enemies, &new.enemies, &mut diffs);
+    diff_map(&old.noise_kinds, &new.noise_kinds, &mut diffs);
+    diff_map(&old.factions, &new.factions, &mut diffs);
+    diff_map(&old.dialogues, &new.dialogues, &mut diffs);
+    diff_map(&old.quests, &new.quests, &mut diffs);
+    diffs
+}
+
+fn diff_map<T: Definition + Serialize>(old: &DefMap<T>, new: &DefMap<T>, out: &mut Vec<DefDiff>) {
+    let mut ids: Vec<&str> = old.ids().chain(new.ids()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    for id in ids {
+        let change = match (old.get(id), new.get(id)) {
+            (None, Some(_)) => DefChange::Added,
+            (Some(_), None) => DefChange::Removed,
+            (Some(old), Some(new)) => {
+                let mut fields = Vec::new();
+                diff_values("", &to_value(old), &to_value(new), &mut fields);
+                if fields.is_empty() {
+                    continue;
+                }
+                DefChange::Changed(fields)
+            }
+            (None, None) => continue,
+        };
+        out.push(DefDiff {
+            kind: T::KIND,
+            id: id.to_owned(),
+            change,
+        });
+    }
+}
+
+/// Goes through JSON text rather than `serde_json::to_value`, which would
+/// widen `f32` fields to `f64` and show `1.2` as `1.2000000476837158`.
+fn to_value(def: &impl Serialize) -> Value {
+    serde_json::to_string(def)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or(Value::Null)
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, out: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort_unstable();
+            keys.dedup();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_values(&path, old, new, out),
+                    (old, new) => out.push(FieldChange {
+                        path,
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = format!("{path}[{i}]");
+                match (old.get(i), new.get(i)) {
+                    (Some(old), Some(new)) => diff_values(&path, old, new, out),
+                    (old, new) => out.push(FieldChange {
+                        path,
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    }),
+                }
+            }
+        }
+        _ if old != new => out.push(FieldChange {
+            path: path.to_owned(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
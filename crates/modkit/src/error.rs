@@ -0,0 +1,268 @@
+use std::{fmt, ops::Range, path::PathBuf};
+
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileFormat {
+    Ron,
+    Toml,
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileFormat::Ron => f.write_str("RON"),
+            FileFormat::Toml => f.write_str("TOML"),
+        }
+    }
+}
+
+/// A 1-based position in a file. `column` counts characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A data file or manifest that could not be parsed.
+///
+/// Displays as `path:line:column: message` followed by the offending line
+/// with a caret under the column, ready to print for whoever edits the file.
+#[derive(Debug, Clone, Error, Serialize)]
+#[error(
+    "{}{}: invalid {format}: {message}{}{}",
+    .path.display(),
+    position(.location),
+    from_mod(.mod_id),
+    render_snippet(.location, .source_line).map(|snippet| format!("\n{snippet}")).unwrap_or_default()
+)]
+pub struct ParseError {
+    /// The mod the file belongs to; `None` when its own `mod.toml` failed.
+    pub mod_id: Option<String>,
+    pub path: PathBuf,
+    pub format: FileFormat,
+    pub message: String,
+    pub location: Option<Location>,
+    /// The full text of the line at `location`, boxed to keep the error small.
+    pub source_line: Option<Box<str>>,
+}
+
+impl ParseError {
+    pub fn ron(
+        mod_id: Option<&str>,
+        path: impl Into<PathBuf>,
+        text: &str,
+        err: ron::error::SpannedError,
+    ) -> Self {
+        // RON reports 1-based lines and byte columns; 0:0 means no position.
+        let location = (err.position.line > 0).then(|| {
+            let line_start = line_start(text, err.position.line);
+            line_start.map_or(
+                Location {
+                    line: err.position.line,
+                    column: err.position.col,
+                },
+                |start| location_at(text, start + err.position.col.saturating_sub(1)),
+            )
+        });
+
+        Self::new(
+            mod_id,
+            path.into(),
+            FileFormat::Ron,
+            err.code.to_string(),
+            text,
+            location,
+        )
+    }
+
+    pub fn toml(
+        mod_id: Option<&str>,
+        path: impl Into<PathBuf>,
+        text: &str,
+        err: toml::de::Error,
+    ) -> Self {
+        let location = err
+            .span()
+            .map(|Range { start, .. }| location_at(text, start));
+
+        Self::new(
+            mod_id,
+            path.into(),
+            FileFormat::Toml,
+            err.message().to_owned(),
+            text,
+            location,
+        )
+    }
+
+    fn new(
+        mod_id: Option<&str>,
+        path: PathBuf,
+        format: FileFormat,
+        message: String,
+        text: &str,
+        location: Option<Location>,
+    ) -> Self {
+        let source_line = location
+            .and_then(|loc| text.lines().nth(loc.line - 1))
+            .map(|line| line.trim_end_matches('\r').into());
+
+        Self {
+            mod_id: mod_id.map(str::to_owned),
+            path,
+            format,
+            message,
+            location,
+            source_line,
+        }
+    }
+
+    /// The offending line with a caret under the error column, e.g.
+    ///
+    /// ```text
+    ///    |
+    ///  4 |     damage: 1.0.0,
+    ///    |                ^
+    /// ```
+    pub fn snippet(&self) -> Option<String> {
+        render_snippet(&self.location, &self.source_line)
+    }
+}
+
+fn position(location: &Option<Location>) -> String {
+    location.map_or_else(String::new, |loc| format!(":{}:{}", loc.line, loc.column))
+}
+
+fn from_mod(mod_id: &Option<String>) -> String {
+    mod_id
+        .as_ref()
+        .map_or_else(String::new, |mod_id| format!(" (mod '{mod_id}')"))
+}
+
+fn render_snippet(location: &Option<Location>, source_line: &Option<Box<str>>) -> Option<String> {
+    let (location, line) = ((*location)?, source_line.as_deref()?);
+
+    // Keep tabs so the caret lines up however the terminal renders them.
+    let padding: String = line
+        .chars()
+        .take(location.column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let number = location.line.to_string();
+    let gutter = " ".repeat(number.len());
+
+    Some(format!(
+        "{gutter} |\n{number} | {line}\n{gutter} | {padding}^"
+    ))
+}
+
+/// Byte offset where 1-based `line` starts, if the text has that many lines.
+fn line_start(text: &str, line: usize) -> Option<usize> {
+    if line == 1 {
+        return Some(0);
+    }
+    text.match_indices('\n').nth(line - 2).map(|(i, _)| i + 1)
+}
+
+/// 1-based line and character column of a byte offset.
+fn location_at(text: &str, offset: usize) -> Location {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Location {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_counts_characters_not_bytes() {
+        let text = "id: \"a\",\nname: \"Épée\", x\n";
+        let offset = text.find('x').unwrap();
+        assert_eq!(
+            location_at(text, offset),
+            Location {
+                line: 2,
+                column: 15
+            }
+        );
+
+        // Offsets inside a multibyte character land on the character itself.
+        let inside = text.find('É').unwrap() + 1;
+        assert_eq!(location_at(text, inside), Location { line: 2, column: 8 });
+        assert_eq!(location_at(text, 0), Location { line: 1, column: 1 });
+        assert_eq!(
+            location_at(text, text.len() + 10),
+            Location { line: 3, column: 1 }
+        );
+    }
+
+    #[test]
+    fn ron_byte_columns_become_character_columns() {
+        let text = "Item(\n    name: \"Épée\" oops,\n)";
+        let err = ron::from_str::<ron::Value>(text).unwrap_err();
+        let err = ParseError::ron(Some("base"), "data/items/epee.ron", text, err);
+
+        let location = err.location.unwrap();
+        assert_eq!(location.line, 2);
+        let line = err.source_line.as_deref().unwrap();
+        assert_eq!(line, "    name: \"Épée\" oops,");
+        assert_eq!(line.chars().nth(location.column - 1), Some('o'));
+    }
+
+    #[test]
+    fn snippet_puts_a_caret_under_the_column() {
+        let err = ParseError {
+            mod_id: Some("base".to_owned()),
+            path: PathBuf::from("data/items/claws.ron"),
+            format: FileFormat::Ron,
+            message: "Expected comma".to_owned(),
+            location: Some(Location {
+                line: 12,
+                column: 13,
+            }),
+            source_line: Some("\tdamage: 1.0.0,".into()),
+        };
+
+        assert_eq!(
+            err.snippet().unwrap(),
+            "   |\n12 | \tdamage: 1.0.0,\n   | \t           ^"
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "data/items/claws.ron:12:13: invalid RON: Expected comma (mod 'base')\n{}",
+                err.snippet().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn no_snippet_without_a_location() {
+        let err = ParseError {
+            mod_id: None,
+            path: PathBuf::from("mod.toml"),
+            format: FileFormat::Toml,
+            message: "missing field `mod_id`".to_owned(),
+            location: None,
+            source_line: None,
+        };
+
+        assert_eq!(err.snippet(), None);
+        assert_eq!(
+            err.to_string(),
+            "mod.toml: invalid TOML: missing field `mod_id`"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Line layout for the RON that modkit writes back to data files.
+
+use std::collections::HashMap;
+
+/// Lines are kept within this many columns where the data allows it.
+const MAX_WIDTH: usize = 100;
+
+/// Groups nested shallower than this always put one entry per line, so a
+/// definition's fields and lists read the same whatever their length.
+const ALWAYS_BREAK_DEPTH: usize = 2;
+
+const INDENT: usize = 4;
+
+/// Lays out RON text: shallow groups and groups too wide for one line get one
+/// entry per line with trailing commas, everything else stays on one line.
+/// A whole float that `original` wrote as an integer at the same field path
+/// is written as that integer again, so `damage: 4` doesn't become `4.0`.
+pub(crate) fn layout(ron: &str, original: &str) -> String {
+    let mut integers = HashMap::new();
+    walk_values(&mut parse(original), &mut Vec::new(), &mut |path, value| {
+        if value.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            integers.insert(path.to_vec(), value.clone());
+        }
+    });
+
+    let mut entry = parse(ron);
+    walk_values(&mut entry, &mut Vec::new(), &mut |path, value| {
+        if let Some(integer) = integers.get(path)
+            && value.strip_suffix(".0") == Some(integer.as_str())
+        {
+            *value = integer.clone();
+        }
+    });
+
+    let mut out = String::new();
+    write_entry(&entry, 0, 0, 0, &mut out);
+    out
+}
+
+/// Whether RON `text` has a `//` or `/* */` comment outside its string and
+/// char literals. Formatting goes through serde, which would drop comments.
+pub fn has_comments(text: &str) -> bool {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        _ if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if matches!(chars.peek(), Some('/' | '*')) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open(char),
+    Close(char),
+    Comma,
+    Colon,
+    Atom(String),
+}
+
+enum Piece {
+    Atom(String),
+    Colon,
+    Group {
+        open: char,
+        close: char,
+        entries: Vec<Vec<Piece>>,
+    },
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' | '[' | '{' => {
+                tokens.push(Token::Open(c));
+                i += 1;
+            }
+            ')' | ']' | '}' => {
+                tokens.push(Token::Close(c));
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            '"' | '\'' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                tokens.push(Token::Atom(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !"()[]{},:\"'".contains(chars[i])
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Atom(chars[start..i].iter().collect()));
+            }
+        }
+    }
+    tokens
+}
+
+fn parse(text: &str) -> Vec<Piece> {
+    let tokens = tokenize(text);
+    let mut pos = 0;
+    parse_entry(&tokens, &mut pos)
+}
+
+/// Pieces up to the next top-level `,` or closing bracket, which is left for
+/// the caller.
+fn parse_entry(tokens: &[Token], pos: &mut usize) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    while let Some(token) = tokens.get(*pos) {
+        match token {
+            Token::Comma | Token::Close(_) => break,
+            Token::Colon => pieces.push(Piece::Colon),
+            Token::Atom(atom) => pieces.push(Piece::Atom(atom.clone())),
+            Token::Open(open) => {
+                *pos += 1;
+                let mut entries = Vec::new();
+                let mut close = *open;
+                while let Some(token) = tokens.get(*pos) {
+                    match token {
+                        Token::Close(c) => {
+                            close = *c;
+                            break;
+                        }
+                        Token::Comma => *pos += 1,
+                        _ => entries.push(parse_entry(tokens, pos)),
+                    }
+                }
+                pieces.push(Piece::Group {
+                    open: *open,
+                    close,
+                    entries,
+                });
+            }
+        }
+        *pos += 1;
+    }
+    pieces
+}
+
+/// Calls `f` with the field path and text of every plain value, e.g.
+/// `["components", "0", "damage"]` for the damage of the first component.
+/// Struct names are left out of the path, as data files may omit them.
+fn walk_values(
+    entry: &mut [Piece],
+    path: &mut Vec<String>,
+    f: &mut impl FnMut(&[String], &mut String),
+) {
+    if let Some(Piece::Atom(value)) = entry.last_mut() {
+        f(path, value);
+    }
+    for piece in entry {
+        let Piece::Group { entries, .. } = piece else {
+            continue;
+        };
+        for (i, child) in entries.iter_mut().enumerate() {
+            let key = match child.iter().position(|piece| matches!(piece, Piece::Colon)) {
+                Some(colon) => flat(&child[..colon]),
+                None => i.to_string(),
+            };
+            path.push(key);
+            walk_values(child, path, f);
+            path.pop();
+        }
+    }
+}
+
+fn flat(pieces: &[Piece]) -> String {
+    let mut out = String::new();
+    for piece in pieces {
+        match piece {
+            Piece::Atom(atom) => out.push_str(atom),
+            Piece::Colon => out.push_str(": "),
+            Piece::Group {
+                open,
+                close,
+                entries,
+            } => {
+                out.push(*open);
+                let entries: Vec<String> = entries.iter().map(|entry| flat(entry)).collect();
+                out.push_str(&entries.join(", "));
+                out.push(*close);
+            }
+        }
+    }
+    out
+}
+
+/// Writes `entry` starting at column `indent`, with `suffix` more columns
+/// following it on its last line.
+fn write_entry(entry: &[Piece], depth: usize, indent: usize, suffix: usize, out: &mut String) {
+    let one_line = flat(entry);
+    let last_group = entry
+        .iter()
+        .rposition(|piece| matches!(piece, Piece::Group { entries, .. } if !entries.is_empty()));
+    let fits = indent + one_line.chars().count() + suffix <= MAX_WIDTH;
+
+    let Some(split) = last_group.filter(|_| depth < ALWAYS_BREAK_DEPTH || !fits) else {
+        out.push_str(&one_line);
+        return;
+    };
+    let Piece::Group {
+        open,
+        close,
+        entries,
+    } = &entry[split]
+    else {
+        unreachable!("split is the index of a group");
+    };
+
+    out.push_str(&flat(&entry[..split]));
+    out.push(*open);
+    out.push('\n');
+    for child in entries {
+        out.push_str(&" ".repeat(indent + INDENT));
+        write_entry(child, depth + 1, indent + INDENT, 1, out);
+        out.push_str(",\n");
+    }
+    out.push_str(&" ".repeat(indent));
+    out.push(*close);
+    out.push_str(&flat(&entry[split + 1..]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::parse_data_file;
+    use crate::to_pretty_ron;
+
+    fn format(text: &str) -> String {
+        let file = parse_data_file(text, "test.ron", None).unwrap();
+        to_pretty_ron(&file, text).unwrap()
+    }
+
+    const GLASS_SHARD: &str = r#"Item(
+    id: "glass_shard",
+    name: "Glass Shard",
+    description: "A sharp piece of glass that can be used in recipes or to make weapons sharper.",
+    components: [
+        Stackable(max: 50),
+        Gadget(
+            slots: [Edge],
+            modifiers: [
+                StatModifier(stat: Damage, factor: 1.5),
+                StatModifier(stat: Cooldown, factor: 1.1),
+            ],
+        ),
+    ],
+)
+"#;
+
+    #[test]
+    fn canonical_file_is_left_alone() {
+        assert_eq!(format(GLASS_SHARD), GLASS_SHARD);
+    }
+
+    #[test]
+    fn formatting_twice_changes_nothing() {
+        let messy = r#"Item(id:"claws",name:"Claws",description:"Sharp.",components:[
+            Weapon(damage:4.0,cooldown:0.25),Damageable(durability:50),
+            Gadget(slots:[Edge,Handle],modifiers:[(stat:Damage,factor:1.5),(stat:Cooldown,factor:1.1),(stat:Damage,factor:2)])])"#;
+
+        let once = format(messy);
+        assert_eq!(format(&once), once);
+        assert!(once.contains("        Weapon(damage: 4.0, cooldown: 0.25),\n"));
+        assert!(once.contains("        Damageable(durability: 50),\n"));
+        assert!(once.contains("                StatModifier(stat: Damage, factor: 2),\n"));
+    }
+
+    #[test]
+    fn base_mod_data_is_canonical() {
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../mods/base/data");
+        for entry in walkdir::WalkDir::new(data) {
+            let path = entry.unwrap().into_path();
+            if path.extension().is_none_or(|ext| ext != "ron") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).unwrap();
+            // `modkit fmt` skips files with comments too.
+            if has_comments(&text) {
+                continue;
+            }
+            assert_eq!(format(&text), text, "{} is not formatted", path.display());
+        }
+    }
+
+    #[test]
+    fn integers_are_matched_by_field_path() {
+        let out = layout(
+            "Weapon(damage: 4.0, cooldown: 1.0)",
+            "Weapon(cooldown: 1.0, damage: 4)",
+        );
+        assert_eq!(out, "Weapon(\n    damage: 4,\n    cooldown: 1.0,\n)");
+    }
+
+    #[test]
+    fn strings_with_brackets_stay_whole() {
+        let text = r#"Tag(id: "a(b", items: ["x, y", "z]"])"#;
+        assert_eq!(
+            layout(text, text),
+            "Tag(\n    id: \"a(b\",\n    items: [\n        \"x, y\",\n        \"z]\",\n    ],\n)"
+        );
+    }
+
+    #[test]
+    fn comments_inside_strings_do_not_count() {
+        assert!(!has_comments(
+            r#"Item(id: "x", description: "see https://example.com")"#
+        ));
+        assert!(!has_comments(r#"Item(id: "x", description: "a \" /* b")"#));
+        assert!(has_comments("Item(id: \"x\") // trailing"));
+        assert!(has_comments("/* header */ Item(id: \"x\")"));
+    }
+}
@@ -1,29 +1,383 @@
-use std::{fs, path::Path};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Component, Path},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use walkdir::WalkDir;
+use thiserror::Error;
+
+use crate::{ignore::MODIGNORE_FILE, vfs::ModFs};
+
+/// Extensions hashed as text when [`HashOptions::normalize_text`] is set.
+pub const TEXT_EXTENSIONS: &[&str] = &["ron", "toml", "rhai", "md", "txt", "json", "ldtk", "csv"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashOptions {
+    /// Hash `.modignore` and files with a [`TEXT_EXTENSIONS`] extension as if they used `\n`
+    /// line endings, so CRLF and LF checkouts of the same mod match.
+    pub normalize_text: bool,
+}
+
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self {
+            normalize_text: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// Canonical path relative to the hashed directory; see [`canonical_path`].
+    pub path: String,
+    /// Size of the hashed (possibly normalized) contents.
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the hashed contents.
+    pub sha256: String,
+}
+
+impl FileEntry {
+    pub fn new(path: String, bytes: &[u8], options: HashOptions) -> Self {
+        let normalized;
+        let bytes = if options.normalize_text && is_text_path(&path) {
+            normalized = normalize_line_endings(bytes);
+            &normalized[..]
+        } else {
+            bytes
+        };
+
+        Self {
+            path,
+            size: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+        }
+    }
+}
+
+/// Every file of a directory with its size and hash, sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentManifest {
+    pub files: Vec<FileEntry>,
+}
+
+impl ContentManifest {
+    pub fn from_dir(root: &Path) -> Result<Self> {
+        Self::from_mod(&ModFs::open_dir(root)?)
+    }
+
+    pub fn from_mod(fs: &ModFs) -> Result<Self> {
+        Self::from_mod_with(fs, HashOptions::default())
+    }
+
+    /// Hashes every file of a mod folder or archive not excluded by its
+    /// `.modignore`. The same files hash the same in either form.
+    pub fn from_mod_with(fs: &ModFs, options: HashOptions) -> Result<Self> {
+        let mut files = Vec::new();
+        for rel in fs.files()? {
+            let bytes = fs.read(&rel)?;
+            files.push((rel, bytes));
+        }
+
+        Ok(Self::from_files(files, options))
+    }
+
+    /// Builds a manifest from `(canonical path, contents)` pairs in any order.
+    pub fn from_files(
+        files: impl IntoIterator<Item = (String, Vec<u8>)>,
+        options: HashOptions,
+    ) -> Self {
+        let mut files: Vec<_> = files
+            .into_iter()
+            .map(|(path, bytes)| FileEntry::new(path, &bytes, options))
+            .collect();
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { files }
+    }
+
+    /// Hash of the whole manifest; equal root hashes mean identical contents.
+    ///
+    /// For every file in path order this hashes the UTF-8 path, a `0` byte, the
+    /// size as a little-endian `u64`, the lowercase hex file hash and a `0` byte.
+    /// Clients and servers compare this value, so the format must not change.
+    pub fn root_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for file in &self.files {
+            hasher.update(file.path.as_bytes());
+            hasher.update([0u8]);
+            hasher.update(file.size.to_le_bytes());
+            hasher.update(file.sha256.as_bytes());
+            hasher.update([0u8]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn get(&self, path: &str) -> Option<&FileEntry> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Files that differ going from `self` (expected) to `other` (actual).
+    pub fn diff(&self, other: &ContentManifest) -> ManifestDiff {
+        let ours: BTreeMap<_, _> = self.files.iter().map(|f| (f.path.as_str(), f)).collect();
+        let theirs: BTreeMap<_, _> = other.files.iter().map(|f| (f.path.as_str(), f)).collect();
+
+        let mut diff = ManifestDiff::default();
+        for (path, file) in &ours {
+            match theirs.get(path) {
+                None => diff.removed.push(path.to_string()),
+                Some(other) if other != file => diff.changed.push(path.to_string()),
+                Some(_) => {}
+            }
+        }
+        for path in theirs.keys() {
+            if !ours.contains_key(path) {
+                diff.added.push(path.to_string());
+            }
+        }
+
+        diff
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// One line per differing file, from the point of view of the expected side:
+/// `missing` files were removed, `unexpected` files were added.
+impl fmt::Display for ManifestDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self
+            .removed
+            .iter()
+            .map(|path| ("missing", path))
+            .chain(self.added.iter().map(|path| ("unexpected", path)))
+            .chain(self.changed.iter().map(|path| ("changed", path)));
+
+        for (i, (status, path)) in lines.enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "  {status:<10} {path}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("mod '{mod_id}' does not match the expected content:\n{diff}")]
+pub struct ContentMismatch {
+    pub mod_id: String,
+    pub diff: ManifestDiff,
+}
+
+/// Compares a local manifest against the one another peer expects.
+pub fn verify_manifest(
+    mod_id: &str,
+    expected: &ContentManifest,
+    actual: &ContentManifest,
+) -> Result<(), ContentMismatch> {
+    let diff = expected.diff(actual);
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        Err(ContentMismatch {
+            mod_id: mod_id.to_owned(),
+            diff,
+        })
+    }
+}
 
 pub fn hash_dir_sha256(root: &Path) -> Result<String> {
-    let mut hasher = Sha256::new();
+    Ok(ContentManifest::from_dir(root)?.root_hash())
+}
+
+/// Encodes a relative path the same way on every platform: normal components
+/// joined with `/`. Fails for non-UTF-8 names and `..`, root or prefix components.
+pub fn canonical_path(rel: &Path) -> Result<String> {
+    let mut out = String::new();
+    for component in rel.components() {
+        match component {
+            Component::Normal(name) => {
+                let name = name
+                    .to_str()
+                    .with_context(|| format!("path {} is not valid UTF-8", rel.display()))?;
+                if !out.is_empty() {
+                    out.push('/');
+                }
+                out.push_str(name);
+            }
+            Component::CurDir => {}
+            _ => bail!("path {} is not a plain relative path", rel.display()),
+        }
+    }
+    Ok(out)
+}
+
+fn is_text_path(path: &str) -> bool {
+    path.rsplit('/').next() == Some(MODIGNORE_FILE)
+        || path
+            .rsplit_once('.')
+            .is_some_and(|(_, ext)| TEXT_EXTENSIONS.iter().any(|t| t.eq_ignore_ascii_case(ext)))
+}
+
+/// Rewrites every `\r\n` as `\n`; lone `\r` bytes are kept.
+fn normalize_line_endings(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().peekable();
+    while let Some(&b) = iter.next() {
+        if b == b'\r' && iter.peek() == Some(&&b'\n') {
+            continue;
+        }
+        out.push(b);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Write, path::PathBuf};
 
-    let mut files: Vec<_> = WalkDir::new(root)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .collect();
+    use zip::write::SimpleFileOptions;
 
-    files.sort_by_key(|e| e.path().to_path_buf());
+    use super::*;
+
+    fn sample_manifest(line_ending: &str) -> ContentManifest {
+        ContentManifest::from_files(
+            vec![
+                ("mod.toml".to_owned(), b"mod_id = \"x\"\n".to_vec()),
+                (
+                    "data/items/a.ron".to_owned(),
+                    format!("Item({line_ending}){line_ending}").into_bytes(),
+                ),
+                ("assets/cat.png".to_owned(), b"\x89PNG\r\n".to_vec()),
+            ],
+            HashOptions::default(),
+        )
+    }
 
-    for entry in files {
-        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
-        hasher.update(rel.to_string_lossy().as_bytes());
-        hasher.update([0u8]);
+    #[test]
+    fn root_hash_format_is_stable() {
+        let manifest = sample_manifest("\r\n");
 
-        let bytes = fs::read(entry.path())?;
-        hasher.update(bytes.len().to_le_bytes());
-        hasher.update(bytes);
+        let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["assets/cat.png", "data/items/a.ron", "mod.toml"]);
+        assert_eq!(
+            manifest.files[1].sha256,
+            "fdf0b296c84094ce8a0ff52bd89ee7d21f6e82b1507059f5730674977b70c243"
+        );
+        assert_eq!(
+            manifest.root_hash(),
+            "3befbff65eda47e8f8ce47aa5e96a5cb7d364ee11592d38f7939b14cebb2e10b"
+        );
     }
 
-    Ok(format!("{:x}", hasher.finalize()))
+    #[test]
+    fn text_files_hash_the_same_with_crlf_and_lf() {
+        assert_eq!(sample_manifest("\r\n"), sample_manifest("\n"));
+
+        // Binary files are never rewritten.
+        let png = &sample_manifest("\n").files[0];
+        assert_eq!(png.size, 6);
+    }
+
+    #[test]
+    fn text_normalization_can_be_disabled() {
+        let raw = HashOptions {
+            normalize_text: false,
+        };
+        let crlf = FileEntry::new("a.ron".to_owned(), b"a\r\n", raw);
+        let lf = FileEntry::new("a.ron".to_owned(), b"a\n", raw);
+        assert_ne!(crlf, lf);
+    }
+
+    #[test]
+    fn canonical_paths_use_forward_slashes() {
+        let rel: PathBuf = ["data", "items", "a.ron"].iter().collect();
+        assert_eq!(canonical_path(&rel).unwrap(), "data/items/a.ron");
+        assert!(canonical_path(Path::new("../a.ron")).is_err());
+    }
+
+    const IGNORE_SAMPLE: &[(&str, &str)] = &[
+        (".git/HEAD", "ref: main"),
+        ("data/items/a.ron", "Item()"),
+        ("data/items/a.ron~", "backup"),
+        ("assets/raw/cat.aseprite", "raw"),
+        ("assets/cat.png", "png"),
+        ("notes.bak", "keep me"),
+        (
+            ".modignore",
+            "# editor files\n*~\n*.bak\n!notes.bak\n/assets/raw/\n",
+        ),
+    ];
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("modkit-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn modignore_excludes_files_from_the_manifest() {
+        let root = temp_path("modignore");
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in IGNORE_SAMPLE {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        let manifest = ContentManifest::from_dir(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                ".modignore",
+                "assets/cat.png",
+                "data/items/a.ron",
+                "notes.bak"
+            ]
+        );
+    }
+
+    #[test]
+    fn zipped_mods_hash_like_folders() {
+        let root = temp_path("zip-dir");
+        let archive = temp_path("zip-archive").with_extension("zip");
+        let _ = fs::remove_dir_all(&root);
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        for (path, contents) in IGNORE_SAMPLE {
+            let dir_path = root.join(path);
+            fs::create_dir_all(dir_path.parent().unwrap()).unwrap();
+            fs::write(dir_path, contents).unwrap();
+
+            // Zipping the folder itself puts everything under one top-level folder.
+            zip.start_file(format!("sample/{path}"), SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(contents.replace('\n', "\r\n").as_bytes())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let from_dir = ContentManifest::from_dir(&root).unwrap();
+        let from_zip = ContentManifest::from_mod(&ModFs::open(&archive).unwrap()).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        fs::remove_file(&archive).unwrap();
+
+        assert_eq!(from_dir, from_zip);
+        assert_eq!(from_dir.root_hash(), from_zip.root_hash());
+    }
 }
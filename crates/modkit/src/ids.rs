@@ -0,0 +1,746 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use thiserror::Error;
+
+use crate::{
+    registry::{ContentRegistry, DefKind},
+    schema::{
+        benches::BenchDef,
+        dialogue::{DialogueCondition, DialogueDef, DialogueEffect, DialogueNode},
+        document::{ContentDoc, DefRef},
+        enemies::{EnemyDef, behavior_script_path},
+        factions::{FactionDef, Stance, StanceEffects},
+        items::ItemDef,
+        loot::{LootEntryDef, LootTableDef},
+        noise::NoiseKindDef,
+        patches::{
+            BenchPatch, DialoguePatch, EnemyPatch, FactionPatch, ItemPatch, LootTablePatch,
+            NoiseKindPatch, PatchDef, QuestPatch, RecipePatch, StatusPatch, TagPatch,
+        },
+        quests::{QuestDef, QuestObjective, QuestRewards},
+        recipes::{ItemStackDef, NoiseDef, RecipeDef, RecipeRequirements},
+        status::StatusDef,
+        tags::TagDef,
+    },
+    tags::{WILDCARD, split_pattern},
+};
+
+/// Separates the mod id from the local id in a full id: `base:glass_shard`.
+pub const NAMESPACE_SEPARATOR: char = ':';
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IdError {
+    #[error("'{id}' is not a valid id; write `local_id` or `mod_id:local_id`")]
+    Malformed { id: String },
+
+    #[error(
+        "'{id}' in mod '{mod_id}' could mean {}; write the full id",
+        candidates.join(" or ")
+    )]
+    Ambiguous {
+        mod_id: String,
+        id: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("'{id}' in mod '{mod_id}' names mod '{namespace}', which it does not depend on")]
+    ForeignNamespace {
+        mod_id: String,
+        id: String,
+        namespace: String,
+    },
+}
+
+/// Splits a full id into its mod id and local id, or returns `None` for a
+/// bare id.
+pub fn split_id(id: &str) -> Option<(&str, &str)> {
+    id.split_once(NAMESPACE_SEPARATOR)
+}
+
+/// The part of an id after the mod id, or the whole id if it is bare.
+pub fn local_id(id: &str) -> &str {
+    split_id(id).map_or(id, |(_, local)| local)
+}
+
+/// `id` in the namespace of `mod_id`, unless it already names one.
+pub fn qualify(mod_id: &str, id: &str) -> String {
+    if split_id(id).is_some() {
+        id.to_owned()
+    } else {
+        format!("{mod_id}{NAMESPACE_SEPARATOR}{id}")
+    }
+}
+
+fn check_id(id: &str) -> Result<(), IdError> {
+    let local = match split_id(id) {
+        Some((namespace, local)) if !namespace.is_empty() => local,
+        Some(_) => "",
+        None => id,
+    };
+    if local.is_empty() || local.contains(NAMESPACE_SEPARATOR) {
+        return Err(IdError::Malformed { id: id.to_owned() });
+    }
+    Ok(())
+}
+
+/// Turns the ids written in one mod's data into full ids.
+///
+/// A bare id names the mod's own definition if it has one, then one from a
+/// mod it lists as a dependency, then one from any other mod it depends on
+/// (`base` included). Two matches at the same step are ambiguous. An id that
+/// matches nothing stays in the mod's own namespace, so validation reports it
+/// as unknown. A full id must name the mod itself or one it depends on. Tags
+/// are definitions too and resolve the same way.
+#[derive(Debug, Clone)]
+pub struct IdResolver<'a> {
+    registry: &'a ContentRegistry,
+    mod_id: String,
+    /// Namespaces to search, nearest first.
+    tiers: Vec<Vec<String>>,
+    /// Full ids the mod is about to define.
+    pending: BTreeSet<(DefKind, String)>,
+}
+
+impl<'a> IdResolver<'a> {
+    /// A resolver for `mod_id`, which must already be in the registry's mods.
+    /// `docs` are the entries it is loading, whose definitions count as
+    /// already present.
+    pub fn new<'d>(
+        registry: &'a ContentRegistry,
+        mod_id: &str,
+        docs: impl IntoIterator<Item = &'d ContentDoc>,
+    ) -> Self {
+        let direct: Vec<String> = registry
+            .loaded_mod(mod_id)
+            .map(|loaded| {
+                loaded
+                    .manifest
+                    .dependencies
+                    .iter()
+                    .map(|dep| dep.mod_id.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let indirect = registry
+            .dependencies_of(mod_id)
+            .into_iter()
+            .filter(|dep| !direct.contains(dep))
+            .collect();
+
+        let pending = docs
+            .into_iter()
+            .filter_map(|doc| {
+                let (kind, id) = match doc {
+                    ContentDoc::Item(def) => (DefKind::Item, &def.id),
+                    ContentDoc::Bench(def) => (DefKind::Bench, &def.id),
+                    ContentDoc::Recipe(def) => (DefKind::Recipe, &def.id),
+                    ContentDoc::LootTable(def) => (DefKind::LootTable, &def.id),
+                    ContentDoc::Tag(def) => (DefKind::Tag, &def.id),
+                    ContentDoc::Status(def) => (DefKind::Status, &def.id),
+                    ContentDoc::Enemy(def) => (DefKind::Enemy, &def.id),
+                    ContentDoc::NoiseKind(def) => (DefKind::NoiseKind, &def.id),
+                    ContentDoc::Faction(def) => (DefKind::Faction, &def.id),
+                    ContentDoc::Dialogue(def) => (DefKind::Dialogue, &def.id),
+                    ContentDoc::Quest(def) => (DefKind::Quest, &def.id),
+                    _ => return None,
+                };
+                Some((kind, qualify(mod_id, id)))
+            })
+            .collect();
+
+        Self {
+            registry,
+            mod_id: mod_id.to_owned(),
+            tiers: vec![vec![mod_id.to_owned()], direct, indirect],
+            pending,
+        }
+    }
+
+    pub fn mod_id(&self) -> &str {
+        &self.mod_id
+    }
+
+    /// The full id of a definition this mod adds or replaces. A bare id is
+    /// always the mod's own.
+    pub fn define(&self, id: &str) -> Result<String, IdError> {
+        check_id(id)?;
+        Ok(qualify(&self.mod_id, id))
+    }
+
+    /// The full id a reference to a `kind` definition names.
+    pub fn resolve(&self, kind: DefKind, id: &str) -> Result<String, IdError> {
+        self.resolve_with(id, |full| {
+            self.pending.contains(&(kind, full.to_owned())) || self.registry.contains(kind, full)
+        })
+    }
+
+    pub fn resolve_tag(&self, tag: &str) -> Result<String, IdError> {
+        self.resolve(DefKind::Tag, tag)
+    }
+
+    /// Resolves the tag in a pattern such as `weapon` or `weapon.*`; the
+    /// bare `*` is left as it is.
+    pub fn resolve_tag_pattern(&self, pattern: &str) -> Result<String, IdError> {
+        if pattern == WILDCARD {
+            return Ok(pattern.to_owned());
+        }
+        let (tag, wildcard) = split_pattern(pattern);
+        let mut full = self.resolve_tag(tag)?;
+        if wildcard {
+            full.push_str(".*");
+        }
+        Ok(full)
+    }
+
+    /// Resolves a behavior profile id to the namespace of the mod that ships
+    /// its script (see [`behavior_script_path`]).
+    pub fn resolve_behavior(&self, behavior: &str) -> Result<String, IdError> {
+        self.resolve_with(behavior, |full| {
+            let Some((mod_id, local)) = split_id(full) else {
+                return false;
+            };
+            self.registry
+                .loaded_mod(mod_id)
+                .is_some_and(|m| m.fs.is_file(&behavior_script_path(local)))
+        })
+    }
+
+    fn resolve_with(&self, id: &str, exists: impl Fn(&str) -> bool) -> Result<String, IdError> {
+        check_id(id)?;
+        if let Some((namespace, _)) = split_id(id) {
+            if !self.tiers.iter().flatten().any(|known| known == namespace) {
+                return Err(IdError::ForeignNamespace {
+                    mod_id: self.mod_id.clone(),
+                    id: id.to_owned(),
+                    namespace: namespace.to_owned(),
+                });
+            }
+            return Ok(id.to_owned());
+        }
+
+        for tier in &self.tiers {
+            let mut candidates: Vec<String> = tier
+                .iter()
+                .map(|namespace| qualify(namespace, id))
+                .filter(|full| exists(full))
+                .collect();
+            match candidates.len() {
+                0 => continue,
+                1 => return Ok(candidates.remove(0)),
+                _ => {
+                    return Err(IdError::Ambiguous {
+                        mod_id: self.mod_id.clone(),
+                        id: id.to_owned(),
+                        candidates,
+                    });
+                }
+            }
+        }
+
+        Ok(qualify(&self.mod_id, id))
+    }
+}
+
+/// Rewrites every id in a definition or entry to its full form.
+pub trait ResolveIds {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError>;
+}
+
+impl ResolveIds for ContentDoc {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        match self {
+            ContentDoc::Item(def) => def.resolve_ids(ids),
+            ContentDoc::Bench(def) => def.resolve_ids(ids),
+            ContentDoc::Recipe(def) => def.resolve_ids(ids),
+            ContentDoc::LootTable(def) => def.resolve_ids(ids),
+            ContentDoc::Tag(def) => def.resolve_ids(ids),
+            ContentDoc::Status(def) => def.resolve_ids(ids),
+            ContentDoc::Enemy(def) => def.resolve_ids(ids),
+            ContentDoc::NoiseKind(def) => def.resolve_ids(ids),
+            ContentDoc::Faction(def) => def.resolve_ids(ids),
+            ContentDoc::Dialogue(def) => def.resolve_ids(ids),
+            ContentDoc::Quest(def) => def.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Item(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Bench(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Recipe(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::LootTable(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Status(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Enemy(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::NoiseKind(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Faction(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Tag(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Dialogue(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Patch(PatchDef::Quest(patch)) => patch.resolve_ids(ids),
+            ContentDoc::Remove(target) => {
+                let (kind, id) = match target {
+                    DefRef::Item(id) => (DefKind::Item, id),
+                    DefRef::Bench(id) => (DefKind::Bench, id),
+                    DefRef::Recipe(id) => (DefKind::Recipe, id),
+                    DefRef::LootTable(id) => (DefKind::LootTable, id),
+                    DefRef::Tag(id) => (DefKind::Tag, id),
+                    DefRef::Status(id) => (DefKind::Status, id),
+                    DefRef::Enemy(id) => (DefKind::Enemy, id),
+                    DefRef::NoiseKind(id) => (DefKind::NoiseKind, id),
+                    DefRef::Faction(id) => (DefKind::Faction, id),
+                    DefRef::Dialogue(id) => (DefKind::Dialogue, id),
+                    DefRef::Quest(id) => (DefKind::Quest, id),
+                };
+                reference(ids, kind, id)
+            }
+            ContentDoc::ComponentRule(_) => Ok(()),
+        }
+    }
+}
+
+impl ResolveIds for ItemDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        tags(ids, &mut self.tags)
+    }
+}
+
+impl ResolveIds for BenchDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        tag_patterns(ids, &mut self.allowed_recipe_tags)
+    }
+}
+
+impl ResolveIds for RecipeDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        reference(ids, DefKind::Bench, &mut self.bench_id)?;
+        tags(ids, &mut self.recipe_tags)?;
+        stacks(ids, &mut self.ingredients)?;
+        stacks(ids, &mut self.outputs)?;
+        noise(ids, self.noise.as_mut())?;
+        requirements(ids, self.requirements.as_mut())
+    }
+}
+
+impl ResolveIds for LootTableDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        loot_entries(ids, &mut self.entries)
+    }
+}
+
+impl ResolveIds for TagDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        match &mut self.parent {
+            Some(parent) => reference(ids, DefKind::Tag, parent)?,
+            // `weapon.melee` sits under whichever `weapon` the mod sees.
+            None => {
+                if let Some((prefix, _)) = local_id(&self.id).rsplit_once('.') {
+                    self.parent = Some(ids.resolve_tag(prefix)?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ResolveIds for StatusDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        noise(ids, self.noise.as_mut())
+    }
+}
+
+impl ResolveIds for EnemyDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        if let Some(drops) = &mut self.drops {
+            reference(ids, DefKind::LootTable, drops)?;
+        }
+        self.behavior = ids.resolve_behavior(&self.behavior)?;
+        Ok(())
+    }
+}
+
+impl ResolveIds for NoiseKindDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        Ok(())
+    }
+}
+
+impl ResolveIds for FactionDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        faction_keys(ids, &mut self.stances)?;
+        rewards(ids, &mut self.rewards)
+    }
+}
+
+impl ResolveIds for DialogueDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        dialogue_nodes(ids, &mut self.nodes)
+    }
+}
+
+impl ResolveIds for QuestDef {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        self.id = ids.define(&self.id)?;
+        objectives(ids, &mut self.objectives)?;
+        quest_rewards(ids, &mut self.rewards)
+    }
+}
+
+impl ResolveIds for ItemPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Item, &mut self.id)?;
+        tags(ids, &mut self.tags)?;
+        tags(ids, &mut self.remove_tags)
+    }
+}
+
+impl ResolveIds for BenchPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Bench, &mut self.id)?;
+        tag_patterns(ids, &mut self.allowed_recipe_tags)
+    }
+}
+
+impl ResolveIds for RecipePatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Recipe, &mut self.id)?;
+        if let Some(bench_id) = &mut self.bench_id {
+            reference(ids, DefKind::Bench, bench_id)?;
+        }
+        tags(ids, &mut self.recipe_tags)?;
+        if let Some(ingredients) = &mut self.ingredients {
+            stacks(ids, ingredients)?;
+        }
+        if let Some(outputs) = &mut self.outputs {
+            stacks(ids, outputs)?;
+        }
+        noise(ids, self.noise.as_mut())?;
+        requirements(ids, self.requirements.as_mut())
+    }
+}
+
+impl ResolveIds for LootTablePatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::LootTable, &mut self.id)?;
+        loot_entries(ids, &mut self.entries)
+    }
+}
+
+impl ResolveIds for StatusPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Status, &mut self.id)?;
+        noise(ids, self.noise.as_mut())
+    }
+}
+
+impl ResolveIds for EnemyPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Enemy, &mut self.id)?;
+        if let Some(drops) = &mut self.drops {
+            reference(ids, DefKind::LootTable, drops)?;
+        }
+        if let Some(behavior) = &mut self.behavior {
+            *behavior = ids.resolve_behavior(behavior)?;
+        }
+        Ok(())
+    }
+}
+
+impl ResolveIds for NoiseKindPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::NoiseKind, &mut self.id)
+    }
+}
+
+impl ResolveIds for TagPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Tag, &mut self.id)?;
+        if let Some(parent) = &mut self.parent {
+            reference(ids, DefKind::Tag, parent)?;
+        }
+        Ok(())
+    }
+}
+
+impl ResolveIds for DialoguePatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Dialogue, &mut self.id)?;
+        dialogue_nodes(ids, &mut self.nodes)
+    }
+}
+
+impl ResolveIds for QuestPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Quest, &mut self.id)?;
+        if let Some(list) = &mut self.objectives {
+            objectives(ids, list)?;
+        }
+        if let Some(rewards) = &mut self.rewards {
+            quest_rewards(ids, rewards)?;
+        }
+        Ok(())
+    }
+}
+
+impl ResolveIds for FactionPatch {
+    fn resolve_ids(&mut self, ids: &IdResolver<'_>) -> Result<(), IdError> {
+        reference(ids, DefKind::Faction, &mut self.id)?;
+        faction_keys(ids, &mut self.stances)?;
+        rewards(ids, &mut self.rewards)
+    }
+}
+
+fn reference(ids: &IdResolver<'_>, kind: DefKind, id: &mut String) -> Result<(), IdError> {
+    *id = ids.resolve(kind, id)?;
+    Ok(())
+}
+
+fn tags(ids: &IdResolver<'_>, tags: &mut [String]) -> Result<(), IdError> {
+    for tag in tags {
+        *tag = ids.resolve_tag(tag)?;
+    }
+    Ok(())
+}
+
+fn tag_patterns(ids: &IdResolver<'_>, patterns: &mut [String]) -> Result<(), IdError> {
+    for pattern in patterns {
+        *pattern = ids.resolve_tag_pattern(pattern)?;
+    }
+    Ok(())
+}
+
+fn stacks(ids: &IdResolver<'_>, stacks: &mut [ItemStackDef]) -> Result<(), IdError> {
+    for stack in stacks {
+        reference(ids, DefKind::Item, &mut stack.item)?;
+    }
+    Ok(())
+}
+
+/// Resolves the faction ids keying `map`.
+fn faction_keys<V>(ids: &IdResolver<'_>, map: &mut BTreeMap<String, V>) -> Result<(), IdError> {
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(faction, value)| Ok((ids.resolve(DefKind::Faction, &faction)?, value)))
+        .collect::<Result<_, IdError>>()?;
+    Ok(())
+}
+
+fn rewards(
+    ids: &IdResolver<'_>,
+    rewards: &mut BTreeMap<Stance, StanceEffects>,
+) -> Result<(), IdError> {
+    for effects in rewards.values_mut() {
+        stacks(ids, &mut effects.items)?;
+    }
+    Ok(())
+}
+
+fn dialogue_nodes(ids: &IdResolver<'_>, nodes: &mut [DialogueNode]) -> Result<(), IdError> {
+    for choice in nodes.iter_mut().flat_map(|node| &mut node.choices) {
+        for condition in &mut choice.conditions {
+            match condition {
+                DialogueCondition::HasItem(stack) => {
+                    reference(ids, DefKind::Item, &mut stack.item)?;
+                }
+                DialogueCondition::Reputation { faction, .. } => {
+                    reference(ids, DefKind::Faction, faction)?;
+                }
+                DialogueCondition::QuestActive(quest) | DialogueCondition::QuestComplete(quest) => {
+                    reference(ids, DefKind::Quest, quest)?;
+                }
+            }
+        }
+        for effect in &mut choice.effects {
+            match effect {
+                DialogueEffect::GiveItem(stack) | DialogueEffect::TakeItem(stack) => {
+                    reference(ids, DefKind::Item, &mut stack.item)?;
+                }
+                DialogueEffect::Reputation { faction, .. } => {
+                    reference(ids, DefKind::Faction, faction)?;
+                }
+                DialogueEffect::StartQuest(quest) => reference(ids, DefKind::Quest, quest)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn objectives(ids: &IdResolver<'_>, objectives: &mut [QuestObjective]) -> Result<(), IdError> {
+    for objective in objectives {
+        match objective {
+            QuestObjective::Collect { item, .. } => reference(ids, DefKind::Item, item)?,
+            QuestObjective::Craft { recipe, .. } => reference(ids, DefKind::Recipe, recipe)?,
+            QuestObjective::Defeat { enemy, .. } => reference(ids, DefKind::Enemy, enemy)?,
+            QuestObjective::ReachZone(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn quest_rewards(ids: &IdResolver<'_>, rewards: &mut QuestRewards) -> Result<(), IdError> {
+    stacks(ids, &mut rewards.items)?;
+    faction_keys(ids, &mut rewards.reputation)
+}
+
+fn noise(ids: &IdResolver<'_>, noise: Option<&mut NoiseDef>) -> Result<(), IdError> {
+    if let Some(noise) = noise {
+        reference(ids, DefKind::NoiseKind, &mut noise.kind)?;
+    }
+    Ok(())
+}
+
+fn requirements(
+    ids: &IdResolver<'_>,
+    requirements: Option<&mut RecipeRequirements>,
+) -> Result<(), IdError> {
+    if let Some(tag) = requirements.and_then(|r| r.needs_equipped_item_tag.as_mut()) {
+        *tag = ids.resolve_tag_pattern(tag)?;
+    }
+    Ok(())
+}
+
+fn loot_entries(ids: &IdResolver<'_>, entries: &mut [LootEntryDef]) -> Result<(), IdError> {
+    for entry in entries {
+        if let Some(item) = &mut entry.item {
+            reference(ids, DefKind::Item, item)?;
+        }
+        if let Some(table) = &mut entry.table {
+            reference(ids, DefKind::LootTable, table)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        loader::ModLoader,
+        testing::{manifest, write_mods},
+    };
+
+    fn items(ids: &[&str]) -> String {
+        let items: Vec<String> = ids
+            .iter()
+            .map(|id| format!(r#"Item(id: "{id}", name: "", description: "", components: [])"#))
+            .collect();
+        format!("[{}]", items.join(", "))
+    }
+
+    /// `top` depends on `mid` and `side`, which both depend on `base`;
+    /// `stray` depends only on `base`.
+    fn registry(name: &str) -> ContentRegistry {
+        let manifests = [
+            manifest("base", &[]),
+            manifest("mid", &["base"]),
+            manifest("side", &["base"]),
+            manifest("top", &["mid", "side"]),
+            manifest("stray", &["base"]),
+        ];
+        let data = [
+            items(&["nail", "bolt", "gear"]),
+            items(&["nail", "bolt", "washer"]),
+            items(&["washer"]),
+            items(&["nail"]),
+            items(&["spring"]),
+        ];
+        let mut files = Vec::new();
+        for ((mod_id, manifest), data) in ["base", "mid", "side", "top", "stray"]
+            .into_iter()
+            .zip(&manifests)
+            .zip(&data)
+        {
+            files.push((format!("{mod_id}/mod.toml"), manifest.as_str()));
+            files.push((format!("{mod_id}/data/items.ron"), data.as_str()));
+        }
+        let files: Vec<(&str, &str)> = files.iter().map(|(p, t)| (p.as_str(), *t)).collect();
+
+        ModLoader::new(write_mods(name, &files)).load().unwrap()
+    }
+
+    #[test]
+    fn bare_ids_prefer_own_then_direct_then_indirect() {
+        let registry = registry("ids-tiers");
+        let ids = IdResolver::new(&registry, "top", []);
+        let item = |id| ids.resolve(DefKind::Item, id).unwrap();
+
+        assert_eq!(item("nail"), "top:nail");
+        assert_eq!(item("bolt"), "mid:bolt");
+        assert_eq!(item("gear"), "base:gear");
+        assert_eq!(item("base:nail"), "base:nail");
+        // Unknown ids stay in the mod's namespace for validation to report.
+        assert_eq!(item("sprocket"), "top:sprocket");
+    }
+
+    #[test]
+    fn matches_at_the_same_tier_are_ambiguous() {
+        let registry = registry("ids-ambiguous");
+        let ids = IdResolver::new(&registry, "top", []);
+        assert_eq!(
+            ids.resolve(DefKind::Item, "washer"),
+            Err(IdError::Ambiguous {
+                mod_id: "top".to_owned(),
+                id: "washer".to_owned(),
+                candidates: vec!["mid:washer".to_owned(), "side:washer".to_owned()],
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_ids_are_rejected() {
+        let registry = registry("ids-malformed");
+        let ids = IdResolver::new(&registry, "top", []);
+        for id in ["a:b:c", ":nail", "base:", ""] {
+            assert_eq!(
+                ids.resolve(DefKind::Item, id),
+                Err(IdError::Malformed { id: id.to_owned() }),
+                "{id:?}"
+            );
+        }
+        assert!(matches!(
+            ids.define("a:b:c"),
+            Err(IdError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn full_ids_must_name_a_dependency() {
+        let registry = registry("ids-foreign");
+        let ids = IdResolver::new(&registry, "top", []);
+        assert_eq!(
+            ids.resolve(DefKind::Item, "stray:spring"),
+            Err(IdError::ForeignNamespace {
+                mod_id: "top".to_owned(),
+                id: "stray:spring".to_owned(),
+                namespace: "stray".to_owned(),
+            })
+        );
+        assert!(matches!(
+            ids.resolve_behavior("stray:guard"),
+            Err(IdError::ForeignNamespace { .. })
+        ));
+    }
+
+    #[test]
+    fn behaviors_resolve_to_the_mod_shipping_the_script() {
+        let files = [
+            ("base/mod.toml", manifest("base", &[])),
+            ("base/scripts/ai/guard.rhai", String::new()),
+            ("base/scripts/ai/patrol.rhai", String::new()),
+            ("extra/mod.toml", manifest("extra", &["base"])),
+            ("extra/scripts/ai/patrol.rhai", String::new()),
+        ];
+        let files: Vec<(&str, &str)> = files.iter().map(|(p, t)| (*p, t.as_str())).collect();
+        let registry = ModLoader::new(write_mods("ids-behaviors", &files))
+            .load()
+            .unwrap();
+        let ids = IdResolver::new(&registry, "extra", []);
+
+        assert_eq!(ids.resolve_behavior("patrol").unwrap(), "extra:patrol");
+        assert_eq!(ids.resolve_behavior("guard").unwrap(), "base:guard");
+        assert_eq!(ids.resolve_behavior("base:patrol").unwrap(), "base:patrol");
+    }
+}
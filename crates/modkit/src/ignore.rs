@@ -0,0 +1,121 @@
+pub const MODIGNORE_FILE: &str = ".modignore";
+
+/// Always ignored, before any `.modignore` rules.
+const DEFAULT_PATTERNS: &[&str] = &[".git/", ".svn/", ".hg/", ".DS_Store", "Thumbs.db"];
+
+/// Gitignore-style rules deciding which files belong to a mod.
+///
+/// One pattern per line; blank lines and `#` comments are skipped. `*` and `?`
+/// match within a path segment, `**` matches across segments, a trailing `/`
+/// only matches directories, and `!` re-includes a previously ignored path.
+/// Patterns containing a `/` are anchored at the mod root; others match the
+/// name of a file or directory at any depth. The last matching rule wins.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: String,
+    anchored: bool,
+    dir_only: bool,
+    negate: bool,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self::parse("")
+    }
+}
+
+impl IgnoreRules {
+    /// Built-in rules followed by the lines of a `.modignore` file.
+    pub fn parse(text: &str) -> Self {
+        let rules = DEFAULT_PATTERNS
+            .iter()
+            .copied()
+            .chain(text.lines())
+            .filter_map(Rule::parse)
+            .collect();
+
+        Self { rules }
+    }
+
+    /// `rel` is a canonical `/`-separated path relative to the mod root.
+    pub fn is_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        let name = rel.rsplit('/').next().unwrap_or(rel);
+
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let subject = if rule.anchored { rel } else { name };
+            if glob_match(rule.pattern.as_bytes(), subject.as_bytes()) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+
+    /// Whether a file or any of the folders above it is ignored.
+    pub fn is_file_ignored(&self, rel: &str) -> bool {
+        rel.match_indices('/')
+            .any(|(i, _)| self.is_ignored(&rel[..i], true))
+            || self.is_ignored(rel, false)
+    }
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.trim_start_matches('/').to_owned();
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            pattern,
+            anchored,
+            dir_only,
+            negate,
+        })
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            // `**/` also matches zero directories.
+            let rest_after_slash = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=text.len())
+                .any(|i| glob_match(rest, &text[i..]) || glob_match(rest_after_slash, &text[i..]))
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => match text {
+            [c, tail @ ..] if *c != b'/' => glob_match(rest, tail),
+            _ => false,
+        },
+        [p, rest @ ..] => match text {
+            [c, tail @ ..] if c == p => glob_match(rest, tail),
+            _ => false,
+        },
+    }
+}
@@ -0,0 +1,474 @@
+use std::{collections::BTreeMap, env, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    default_ron_options,
+    error::ParseError,
+    ids::{IdError, IdResolver},
+    registry::{ContentRegistry, DefKind, DefSource},
+    schema::{
+        benches::BenchDef, enemies::EnemyDef, factions::FactionDef, items::ItemDef,
+        quests::QuestDef, recipes::RecipeDef, status::StatusDef,
+    },
+    vfs::ModFs,
+};
+
+pub const LANG_DIR: &str = "lang";
+
+/// The language definitions are written in. Their own `name` and
+/// `description` are the text of last resort for every locale.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// One localized string and the lang file that last set it.
+#[derive(Debug, Clone)]
+pub struct Translation {
+    pub text: String,
+    pub source: DefSource,
+}
+
+/// Localized strings of every loaded mod, by locale and then key.
+///
+/// Mods load in order, so a later mod's `lang/<locale>.ron` overrides keys an
+/// earlier mod set.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    locales: BTreeMap<String, BTreeMap<String, Translation>>,
+}
+
+impl Translations {
+    pub fn insert(&mut self, locale: &str, key: String, text: String, source: DefSource) {
+        self.locales
+            .entry(locale.to_owned())
+            .or_default()
+            .insert(key, Translation { text, source });
+    }
+
+    /// Every locale some mod ships a lang file for, sorted.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.locales.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, locale: &str, key: &str) -> Option<&str> {
+        self.entry(locale, key).map(|t| t.text.as_str())
+    }
+
+    pub fn entry(&self, locale: &str, key: &str) -> Option<&Translation> {
+        self.locales.get(locale)?.get(key)
+    }
+
+    /// Every key of `locale` with its translation, sorted by key.
+    pub fn entries(&self, locale: &str) -> impl Iterator<Item = (&str, &Translation)> {
+        self.locales
+            .get(locale)
+            .into_iter()
+            .flatten()
+            .map(|(key, t)| (key.as_str(), t))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locales.is_empty()
+    }
+
+    /// Adds every string of `other`, replacing keys both have.
+    pub fn extend(&mut self, other: Translations) {
+        for (locale, strings) in other.locales {
+            self.locales.entry(locale).or_default().extend(strings);
+        }
+    }
+}
+
+pub fn item_name_key(id: &str) -> String {
+    format!("item.{id}.name")
+}
+
+pub fn item_description_key(id: &str) -> String {
+    format!("item.{id}.description")
+}
+
+pub fn bench_name_key(id: &str) -> String {
+    format!("bench.{id}.name")
+}
+
+pub fn recipe_name_key(id: &str) -> String {
+    format!("recipe.{id}.name")
+}
+
+pub fn status_name_key(id: &str) -> String {
+    format!("status.{id}.name")
+}
+
+pub fn status_description_key(id: &str) -> String {
+    format!("status.{id}.description")
+}
+
+pub fn enemy_name_key(id: &str) -> String {
+    format!("enemy.{id}.name")
+}
+
+pub fn faction_name_key(id: &str) -> String {
+    format!("faction.{id}.name")
+}
+
+pub fn quest_name_key(id: &str) -> String {
+    format!("quest.{id}.name")
+}
+
+pub fn quest_description_key(id: &str) -> String {
+    format!("quest.{id}.description")
+}
+
+/// A localizable string of a definition and its English text.
+#[derive(Debug, Clone)]
+pub struct TextKey<'a> {
+    pub kind: DefKind,
+    pub def_id: &'a str,
+    pub key: String,
+    pub fallback: &'a str,
+}
+
+/// Every localization key the registry's definitions use, by kind and id.
+pub fn text_keys(registry: &ContentRegistry) -> Vec<TextKey<'_>> {
+    let mut keys = Vec::new();
+    for item in registry.items.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Item,
+            def_id: &item.id,
+            key: item_name_key(&item.id),
+            fallback: &item.name,
+        });
+        keys.push(TextKey {
+            kind: DefKind::Item,
+            def_id: &item.id,
+            key: item_description_key(&item.id),
+            fallback: &item.description,
+        });
+    }
+    for bench in registry.benches.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Bench,
+            def_id: &bench.id,
+            key: bench_name_key(&bench.id),
+            fallback: &bench.name,
+        });
+    }
+    for recipe in registry.recipes.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Recipe,
+            def_id: &recipe.id,
+            key: recipe_name_key(&recipe.id),
+            fallback: &recipe.name,
+        });
+    }
+    for status in registry.statuses.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Status,
+            def_id: &status.id,
+            key: status_name_key(&status.id),
+            fallback: &status.name,
+        });
+        keys.push(TextKey {
+            kind: DefKind::Status,
+            def_id: &status.id,
+            key: status_description_key(&status.id),
+            fallback: &status.description,
+        });
+    }
+    for enemy in registry.enemies.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Enemy,
+            def_id: &enemy.id,
+            key: enemy_name_key(&enemy.id),
+            fallback: &enemy.name,
+        });
+    }
+    for faction in registry.factions.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Faction,
+            def_id: &faction.id,
+            key: faction_name_key(&faction.id),
+            fallback: &faction.name,
+        });
+    }
+    for quest in registry.quests.iter() {
+        keys.push(TextKey {
+            kind: DefKind::Quest,
+            def_id: &quest.id,
+            key: quest_name_key(&quest.id),
+            fallback: &quest.name,
+        });
+        keys.push(TextKey {
+            kind: DefKind::Quest,
+            def_id: &quest.id,
+            key: quest_description_key(&quest.id),
+            fallback: &quest.description,
+        });
+    }
+    keys
+}
+
+/// The definition kind a key belongs to by its prefix, e.g. `item.` keys to
+/// items. Keys with other prefixes (UI text and the like) belong to none.
+pub fn key_kind(key: &str) -> Option<DefKind> {
+    match key.split_once('.')?.0 {
+        "item" => Some(DefKind::Item),
+        "bench" => Some(DefKind::Bench),
+        "recipe" => Some(DefKind::Recipe),
+        "status" => Some(DefKind::Status),
+        "enemy" => Some(DefKind::Enemy),
+        "faction" => Some(DefKind::Faction),
+        "quest" => Some(DefKind::Quest),
+        _ => None,
+    }
+}
+
+/// Every `lang/<locale>.ron` file of a mod as `(locale, path)`, sorted by path.
+/// Files excluded by the mod's `.modignore` are skipped.
+pub fn lang_files(fs: &ModFs) -> Result<Vec<(String, String)>> {
+    let prefix = format!("{LANG_DIR}/");
+    Ok(fs
+        .files()?
+        .into_iter()
+        .filter_map(|rel| {
+            let name = rel.strip_prefix(&prefix)?;
+            let locale = name.strip_suffix(".ron")?;
+            (!locale.contains('/') && !locale.is_empty()).then(|| (locale.to_owned(), rel.clone()))
+        })
+        .collect())
+}
+
+/// Parses a lang file: a RON map from key to text, e.g.
+/// `{ "item.glass_shard.name": "Éclat de verre" }`.
+pub fn parse_lang_file(
+    text: &str,
+    path: impl Into<PathBuf>,
+    mod_id: Option<&str>,
+) -> Result<BTreeMap<String, String>, ParseError> {
+    default_ron_options()
+        .from_str(text)
+        .map_err(|err| ParseError::ron(mod_id, path, text, err))
+}
+
+/// Loads the lang files of the mod `ids` resolves for. The id in each
+/// definition key is resolved like any other reference, so
+/// `item.glass_shard.name` becomes `item.base:glass_shard.name`.
+pub fn load_lang_files(fs: &ModFs, ids: &IdResolver<'_>) -> Result<Translations> {
+    let mod_id = ids.mod_id();
+    let mut translations = Translations::default();
+    for (locale, rel) in lang_files(fs)? {
+        let text = fs.read_to_string(&rel)?;
+        let strings = parse_lang_file(&text, fs.display_path(&rel), Some(mod_id))?;
+        let source = DefSource {
+            mod_id: mod_id.to_owned(),
+            file: PathBuf::from(&rel),
+        };
+        for (key, text) in strings {
+            let key = resolve_key(&key, ids)
+                .with_context(|| format!("resolving ids in {}", fs.display_path(&rel).display()))?;
+            translations.insert(&locale, key, text, source.clone());
+        }
+    }
+    Ok(translations)
+}
+
+/// Resolves the id in a `<kind>.<id>.<field>` key; other keys are unchanged.
+fn resolve_key(key: &str, ids: &IdResolver<'_>) -> Result<String, IdError> {
+    let (Some(kind), Some((prefix, rest))) = (key_kind(key), key.split_once('.')) else {
+        return Ok(key.to_owned());
+    };
+    let Some((id, field)) = rest.rsplit_once('.') else {
+        return Ok(key.to_owned());
+    };
+    Ok(format!("{prefix}.{}.{field}", ids.resolve(kind, id)?))
+}
+
+/// Looks up text in the active locale.
+///
+/// A key missing from the active locale falls back to its base language
+/// (`pt-BR` to `pt`), then to [`DEFAULT_LOCALE`], then to the English text
+/// written in the definition itself.
+#[derive(Debug, Clone)]
+pub struct Localizer<'a> {
+    translations: &'a Translations,
+    chain: Vec<String>,
+}
+
+impl<'a> Localizer<'a> {
+    pub fn new(translations: &'a Translations, locale: &str) -> Self {
+        let mut chain = vec![locale.to_owned()];
+        if let Some((language, _)) = locale.split_once('-') {
+            chain.push(language.to_owned());
+        }
+        chain.push(DEFAULT_LOCALE.to_owned());
+        chain.dedup();
+
+        Self {
+            translations,
+            chain,
+        }
+    }
+
+    /// Uses the locale of the environment (see [`system_locale`]), or
+    /// [`DEFAULT_LOCALE`] if none is set.
+    pub fn from_env(translations: &'a Translations) -> Self {
+        let locale = system_locale().unwrap_or_else(|| DEFAULT_LOCALE.to_owned());
+        Self::new(translations, &locale)
+    }
+
+    /// The active locale.
+    pub fn locale(&self) -> &str {
+        &self.chain[0]
+    }
+
+    /// The text for `key`, or `fallback` if no locale in the chain has it.
+    pub fn text<'s>(&'s self, key: &str, fallback: &'s str) -> &'s str {
+        self.chain
+            .iter()
+            .find_map(|locale| self.translations.get(locale, key))
+            .unwrap_or(fallback)
+    }
+
+    pub fn item_name<'s>(&'s self, item: &'s ItemDef) -> &'s str {
+        self.text(&item_name_key(&item.id), &item.name)
+    }
+
+    pub fn item_description<'s>(&'s self, item: &'s ItemDef) -> &'s str {
+        self.text(&item_description_key(&item.id), &item.description)
+    }
+
+    pub fn bench_name<'s>(&'s self, bench: &'s BenchDef) -> &'s str {
+        self.text(&bench_name_key(&bench.id), &bench.name)
+    }
+
+    pub fn recipe_name<'s>(&'s self, recipe: &'s RecipeDef) -> &'s str {
+        self.text(&recipe_name_key(&recipe.id), &recipe.name)
+    }
+
+    pub fn status_name<'s>(&'s self, status: &'s StatusDef) -> &'s str {
+        self.text(&status_name_key(&status.id), &status.name)
+    }
+
+    pub fn status_description<'s>(&'s self, status: &'s StatusDef) -> &'s str {
+        self.text(&status_description_key(&status.id), &status.description)
+    }
+
+    pub fn enemy_name<'s>(&'s self, enemy: &'s EnemyDef) -> &'s str {
+        self.text(&enemy_name_key(&enemy.id), &enemy.name)
+    }
+
+    pub fn faction_name<'s>(&'s self, faction: &'s FactionDef) -> &'s str {
+        self.text(&faction_name_key(&faction.id), &faction.name)
+    }
+
+    pub fn quest_name<'s>(&'s self, quest: &'s QuestDef) -> &'s str {
+        self.text(&quest_name_key(&quest.id), &quest.name)
+    }
+
+    pub fn quest_description<'s>(&'s self, quest: &'s QuestDef) -> &'s str {
+        self.text(&quest_description_key(&quest.id), &quest.description)
+    }
+
+    /// Text written only in lang files, such as dialogue lines. Shows the key
+    /// itself if no locale has it.
+    pub fn line<'s>(&'s self, key: &'s str) -> &'s str {
+        self.text(key, key)
+    }
+}
+
+/// The user's locale from `LC_ALL`, `LC_MESSAGES` or `LANG`, written like
+/// lang file names: `pt_BR.UTF-8` becomes `pt-BR`. `C` and `POSIX` count as
+/// unset.
+pub fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| normalize_locale(&value))
+}
+
+fn normalize_locale(value: &str) -> Option<String> {
+    let locale = value.split(['.', '@']).next()?.replace('_', "-");
+    (!locale.is_empty() && locale != "C" && locale != "POSIX").then_some(locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        loader::ModLoader,
+        testing::{manifest, write_mods},
+    };
+
+    fn source() -> DefSource {
+        DefSource {
+            mod_id: "base".to_owned(),
+            file: PathBuf::from("lang/test.ron"),
+        }
+    }
+
+    fn translations(strings: &[(&str, &str, &str)]) -> Translations {
+        let mut translations = Translations::default();
+        for &(locale, key, text) in strings {
+            translations.insert(locale, key.to_owned(), text.to_owned(), source());
+        }
+        translations
+    }
+
+    #[test]
+    fn text_falls_back_through_the_base_language_and_english() {
+        let translations = translations(&[
+            ("pt-BR", "a", "a pt-BR"),
+            ("pt", "a", "a pt"),
+            ("pt", "b", "b pt"),
+            ("en", "c", "c en"),
+        ]);
+        let localizer = Localizer::new(&translations, "pt-BR");
+        assert_eq!(localizer.locale(), "pt-BR");
+        assert_eq!(localizer.text("a", "a def"), "a pt-BR");
+        assert_eq!(localizer.text("b", "b def"), "b pt");
+        assert_eq!(localizer.text("c", "c def"), "c en");
+        assert_eq!(localizer.text("d", "d def"), "d def");
+        assert_eq!(localizer.line("d"), "d");
+    }
+
+    #[test]
+    fn english_is_not_searched_twice() {
+        let translations = translations(&[("en", "a", "a en")]);
+        let localizer = Localizer::new(&translations, DEFAULT_LOCALE);
+        assert_eq!(localizer.chain, ["en"]);
+        assert_eq!(localizer.text("a", "a def"), "a en");
+    }
+
+    #[test]
+    fn system_locales_are_written_like_lang_files() {
+        assert_eq!(normalize_locale("pt_BR.UTF-8").as_deref(), Some("pt-BR"));
+        assert_eq!(normalize_locale("de_DE@euro").as_deref(), Some("de-DE"));
+        assert_eq!(normalize_locale("fr").as_deref(), Some("fr"));
+        assert_eq!(normalize_locale("C.UTF-8"), None);
+        assert_eq!(normalize_locale("POSIX"), None);
+        assert_eq!(normalize_locale(""), None);
+    }
+
+    #[test]
+    fn definition_keys_are_resolved_and_others_kept() {
+        let base = manifest("base", &[]);
+        let dir = write_mods(
+            "lang-keys",
+            &[
+                ("base/mod.toml", &base),
+                (
+                    "base/data/nail.ron",
+                    r#"Item(id: "nail", name: "Nail", description: "", components: [])"#,
+                ),
+                (
+                    "base/lang/fr.ron",
+                    r#"{ "item.nail.name": "Clou", "ui.menu.quit": "Quitter" }"#,
+                ),
+            ],
+        );
+        let registry = ModLoader::new(&dir).load().unwrap();
+        let translations = &registry.translations;
+        assert_eq!(translations.get("fr", "item.base:nail.name"), Some("Clou"));
+        assert_eq!(translations.get("fr", "ui.menu.quit"), Some("Quitter"));
+    }
+}
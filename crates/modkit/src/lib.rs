@@ -1,6 +1,28 @@
+pub mod diff;
+pub mod error;
+mod format;
 pub mod hashing;
+pub mod ids;
+pub mod ignore;
+pub mod lang;
+pub mod loader;
+pub mod loot;
 pub mod manifest;
+pub mod migrate;
+pub mod noise;
+pub mod recipe_graph;
+pub mod registry;
+pub mod resolve;
 pub mod schema;
+pub mod tags;
+#[cfg(test)]
+mod testing;
+pub mod tinkering;
+pub mod validate;
+pub mod vfs;
+pub mod watch;
+
+pub use format::has_comments;
 
 pub fn default_ron_options() -> ron::options::Options {
     ron::options::Options::default().with_default_extension(
@@ -8,3 +30,18 @@ pub fn default_ron_options() -> ron::options::Options {
             | ron::extensions::Extensions::IMPLICIT_SOME,
     )
 }
+
+/// Canonical layout used when modkit writes RON data files back to disk.
+/// `original` is the text `value` was read from; numbers it wrote as integers
+/// stay integers.
+pub fn to_pretty_ron<T: serde::Serialize>(value: &T, original: &str) -> ron::Result<String> {
+    let config = ron::ser::PrettyConfig::new().struct_names(true).extensions(
+        ron::extensions::Extensions::UNWRAP_VARIANT_NEWTYPES
+            | ron::extensions::Extensions::IMPLICIT_SOME,
+    );
+
+    let text = default_ron_options().to_string_pretty(value, config)?;
+    let mut text = format::layout(&text, original);
+    text.push('\n');
+    Ok(text)
+}
@@ -0,0 +1,350 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    default_ron_options,
+    error::ParseError,
+    ids::{IdResolver, ResolveIds},
+    lang::load_lang_files,
+    manifest::ModManifest,
+    migrate::{CURRENT_MOD_API_VERSION, check_api_version, migrate_data},
+    registry::{ContentRegistry, DefSource, LoadedMod},
+    resolve::resolve_load_order,
+    schema::document::DataFile,
+    vfs::{ModFs, is_zip_path},
+};
+
+pub const MANIFEST_FILE: &str = "mod.toml";
+pub const DATA_DIR: &str = "data";
+
+/// The id of the game's own content, which is always loaded first.
+pub const BASE_MOD_ID: &str = "base";
+
+/// A mod folder or archive found under the mods directory, with its parsed manifest.
+#[derive(Debug, Clone)]
+pub struct DiscoveredMod {
+    pub manifest: ModManifest,
+    pub fs: ModFs,
+}
+
+/// Discovers mods under a directory and loads their data into a [`ContentRegistry`].
+#[derive(Debug, Clone)]
+pub struct ModLoader {
+    mods_dir: PathBuf,
+    enabled: Option<Vec<String>>,
+}
+
+impl ModLoader {
+    pub fn new(mods_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            mods_dir: mods_dir.into(),
+            enabled: None,
+        }
+    }
+
+    /// Restricts loading to these mods (plus `base` and their dependencies),
+    /// in this configured order. By default every discovered mod is loaded.
+    pub fn with_enabled(mut self, enabled: Vec<String>) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    pub fn mods_dir(&self) -> &Path {
+        &self.mods_dir
+    }
+
+    /// Returns every immediate subfolder or `.zip` of the mods directory that
+    /// has a `mod.toml`, with `base` first and the rest sorted by mod id.
+    pub fn discover(&self) -> Result<Vec<DiscoveredMod>> {
+        let entries = fs::read_dir(&self.mods_dir)
+            .with_context(|| format!("reading mods directory {}", self.mods_dir.display()))?;
+
+        let mut mods = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if !path.is_dir() && !is_zip_path(&path) {
+                continue;
+            }
+            let fs = ModFs::open(&path)?;
+            if !fs.is_file(MANIFEST_FILE) {
+                continue;
+            }
+
+            let manifest = read_manifest(&fs)?;
+            if let Some(other) = mods
+                .iter()
+                .find(|m: &&DiscoveredMod| m.manifest.mod_id == manifest.mod_id)
+            {
+                bail!(
+                    "mod id '{}' is declared by both {} and {}",
+                    manifest.mod_id,
+                    other.fs.root().display(),
+                    path.display(),
+                );
+            }
+
+            mods.push(DiscoveredMod { manifest, fs });
+        }
+
+        mods.sort_by(|a, b| {
+            let a_id = &a.manifest.mod_id;
+            let b_id = &b.manifest.mod_id;
+            (a_id != BASE_MOD_ID, a_id).cmp(&(b_id != BASE_MOD_ID, b_id))
+        });
+
+        Ok(mods)
+    }
+
+    /// Discovers the enabled mods and sorts them so dependencies load first.
+    pub fn resolve(&self) -> Result<Vec<DiscoveredMod>> {
+        let mods = self.discover()?;
+        Ok(resolve_load_order(mods, self.enabled.as_deref())?)
+    }
+
+    /// Loads the data of every enabled mod, in resolved load order.
+    pub fn load(&self) -> Result<ContentRegistry> {
+        let mut registry = ContentRegistry::default();
+        for discovered in self.resolve()? {
+            load_mod(&mut registry, discovered)?;
+        }
+        Ok(registry)
+    }
+}
+
+pub fn read_manifest(fs: &ModFs) -> Result<ModManifest> {
+    let text = fs.read_to_string(MANIFEST_FILE)?;
+    let manifest = toml::from_str(&text)
+        .map_err(|err| ParseError::toml(None, fs.display_path(MANIFEST_FILE), &text, err))?;
+    Ok(manifest)
+}
+
+/// Loads every `data/**/*.ron` file of a mod into the registry.
+///
+/// A file holds one entry or a list of them, and each entry's kind comes from
+/// its struct name, so `data/items/glass_shard.ron` and a single `data/items.ron`
+/// listing every item load the same. Entries are merged in path order, then in
+/// the order written; see [`ContentRegistry::apply`]. Data written for an older
+/// `mod_api_version` is migrated in memory first. The mod's `lang/*.ron` files
+/// are loaded after its data.
+pub fn load_mod(registry: &mut ContentRegistry, discovered: DiscoveredMod) -> Result<()> {
+    check_api_version(&discovered.manifest)?;
+
+    let fs = &discovered.fs;
+    let mod_id = discovered.manifest.mod_id.clone();
+    let api_version = discovered.manifest.mod_api_version;
+
+    let mut files = Vec::new();
+    for rel in data_files(fs)? {
+        let mut text = fs.read_to_string(&rel)?;
+        let migrated = api_version < CURRENT_MOD_API_VERSION;
+        if migrated {
+            text = migrate_data(&text, api_version);
+        }
+        let file =
+            parse_data_file(&text, fs.display_path(&rel), Some(&mod_id)).map_err(|mut err| {
+                // The position and snippet are in the migrated text, which can
+                // differ from the file on disk.
+                if migrated {
+                    err.message = format!(
+                        "{} (in the text migrated from mod_api_version {api_version}; \
+                     `modkit migrate` writes it to disk)",
+                        err.message
+                    );
+                }
+                err
+            })?;
+
+        files.push((rel, file.into_docs()));
+    }
+
+    registry.mods.push(LoadedMod {
+        manifest: discovered.manifest,
+        fs: discovered.fs,
+    });
+
+    let mut docs = Vec::new();
+    {
+        let loaded = registry.mods.last().expect("mod was just pushed");
+        let ids = IdResolver::new(registry, &mod_id, files.iter().flat_map(|(_, d)| d));
+        for (rel, file_docs) in files {
+            let source = DefSource {
+                mod_id: mod_id.clone(),
+                file: PathBuf::from(&rel),
+            };
+            for mut doc in file_docs {
+                doc.resolve_ids(&ids).with_context(|| {
+                    format!(
+                        "resolving ids in {}",
+                        loaded.fs.display_path(&rel).display()
+                    )
+                })?;
+                docs.push((doc, source.clone()));
+            }
+        }
+    }
+
+    for (doc, source) in docs {
+        registry.apply(doc, source)?;
+    }
+
+    let loaded = registry.mods.last().expect("mod was just pushed");
+    let translations = load_lang_files(&loaded.fs, &IdResolver::new(registry, &mod_id, []))?;
+    registry.translations.extend(translations);
+
+    Ok(())
+}
+
+/// Canonical path of every `data/**/*.ron` file of a mod, sorted. Files
+/// excluded by the mod's `.modignore` are skipped.
+pub fn data_files(fs: &ModFs) -> Result<Vec<String>> {
+    let prefix = format!("{DATA_DIR}/");
+    let mut files = fs.files()?;
+    files.retain(|rel| rel.starts_with(&prefix) && rel.ends_with(".ron"));
+    Ok(files)
+}
+
+/// Parses one data file; `path` and `mod_id` only locate it in errors.
+pub fn parse_data_file(
+    text: &str,
+    path: impl Into<PathBuf>,
+    mod_id: Option<&str>,
+) -> Result<DataFile, ParseError> {
+    let options = default_ron_options();
+    let parsed = if is_list(text) {
+        options.from_str(text).map(DataFile::List)
+    } else {
+        options
+            .from_str(text)
+            .map(|doc| DataFile::Single(Box::new(doc)))
+    };
+    parsed.map_err(|err| ParseError::ron(mod_id, path, text, err))
+}
+
+/// Whether the top-level value of a RON file is a list, looking past comments
+/// and `#![enable(..)]` attributes.
+fn is_list(text: &str) -> bool {
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start_matches('\u{feff}').trim_start();
+        let skipped = if let Some(after) = rest.strip_prefix("//") {
+            after.split_once('\n').map(|(_, next)| next)
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            after.split_once("*/").map(|(_, next)| next)
+        } else if let Some(after) = rest.strip_prefix("#!") {
+            after.split_once(']').map(|(_, next)| next)
+        } else {
+            return rest.starts_with('[');
+        };
+
+        match skipped {
+            Some(next) => rest = next,
+            None => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        registry::MergeError,
+        schema::document::ContentDoc,
+        testing::{manifest, write_mods},
+    };
+
+    const NAIL: &str = r#"Item(id: "nail", name: "Nail", description: "", components: [])"#;
+    const BOLT: &str = r#"Item(id: "bolt", name: "Bolt", description: "", components: [])"#;
+
+    fn ids(file: DataFile) -> Vec<String> {
+        file.into_docs()
+            .into_iter()
+            .map(|doc| match doc {
+                ContentDoc::Item(item) => item.id,
+                other => panic!("expected an item, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_entry_and_list_files_load_alike() {
+        let single = parse_data_file(NAIL, "nail.ron", None).unwrap();
+        assert!(matches!(single, DataFile::Single(_)));
+        assert_eq!(ids(single), ["nail"]);
+
+        let list = parse_data_file(&format!("[{NAIL}, {BOLT}]"), "items.ron", None).unwrap();
+        assert!(matches!(list, DataFile::List(_)));
+        assert_eq!(ids(list), ["nail", "bolt"]);
+    }
+
+    #[test]
+    fn lists_are_found_past_comments_and_attributes() {
+        assert!(is_list("// items\n[]"));
+        assert!(is_list("/* items\n */ [\n]"));
+        assert!(is_list("\u{feff}#![enable(implicit_some)]\n// items\n[]"));
+        assert!(!is_list("// [not a list]\nItem()"));
+        assert!(!is_list("/* unterminated ["));
+
+        let text = format!("#![enable(implicit_some)]\n// hardware\n[{NAIL}, {BOLT}]");
+        assert_eq!(
+            ids(parse_data_file(&text, "items.ron", None).unwrap()),
+            ["nail", "bolt"]
+        );
+    }
+
+    #[test]
+    fn an_id_defined_in_two_files_is_an_error() {
+        let base = manifest("base", &[]);
+        let dir = write_mods(
+            "loader-duplicate",
+            &[
+                ("base/mod.toml", &base),
+                ("base/data/a.ron", NAIL),
+                ("base/data/b.ron", &format!("[{BOLT}, {NAIL}]")),
+            ],
+        );
+
+        let err = ModLoader::new(&dir).load().unwrap_err();
+        let Some(MergeError::DuplicateId {
+            id, at, existing, ..
+        }) = err.downcast_ref()
+        else {
+            panic!("expected a duplicate id error, got {err:#}");
+        };
+        assert_eq!(id, "base:nail");
+        assert_eq!(at.file, Path::new("data/b.ron"));
+        assert_eq!(existing.file, Path::new("data/a.ron"));
+    }
+
+    #[test]
+    fn errors_in_migrated_text_say_so() {
+        let dir = write_mods(
+            "loader-migrated-error",
+            &[
+                (
+                    "base/mod.toml",
+                    "mod_id = \"base\"\nname = \"Base\"\nversion = \"1.0.0\"\nmod_api_version = 1\n",
+                ),
+                (
+                    "base/data/claws.ron",
+                    "Item(id: \"claws\", components: [Gadget(slot: Edge)])",
+                ),
+            ],
+        );
+
+        let err = ModLoader::new(&dir).load().unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert!(
+            err.message.contains("migrated from mod_api_version 1"),
+            "{}",
+            err.message
+        );
+        assert_eq!(
+            err.source_line.as_deref(),
+            Some("Item(id: \"claws\", components: [Gadget(slots: [Edge])])")
+        );
+    }
+}
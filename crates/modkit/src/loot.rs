@@ -0,0 +1,505 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    registry::ContentRegistry,
+    schema::loot::{LootCondition, LootEntryDef},
+};
+
+/// Where loot is being rolled, checked against entry conditions.
+#[derive(Debug, Clone, Default)]
+pub struct LootContext {
+    pub zone_tags: Vec<String>,
+    pub difficulty: u32,
+}
+
+impl LootContext {
+    pub fn allows(&self, conditions: &[LootCondition]) -> bool {
+        conditions.iter().all(|condition| match condition {
+            LootCondition::ZoneTag(tag) => self.zone_tags.contains(tag),
+            LootCondition::MinDifficulty(min) => self.difficulty >= *min,
+            LootCondition::MaxDifficulty(max) => self.difficulty <= *max,
+        })
+    }
+}
+
+/// One stack of dropped items, never larger than the item's `Stackable` max.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemDrop {
+    pub item: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LootError {
+    #[error("unknown loot table '{0}'")]
+    UnknownTable(String),
+
+    #[error("loot table '{table}' drops unknown item '{item}'")]
+    UnknownItem { table: String, item: String },
+
+    #[error("loot tables roll each other in a cycle: {}", cycle.join(" -> "))]
+    Cycle { cycle: Vec<String> },
+}
+
+/// Rolls loot tables from a registry.
+///
+/// All randomness comes from the caller's [`fastrand::Rng`], so the same seed,
+/// registry and context always produce the same drops.
+#[derive(Debug, Clone, Copy)]
+pub struct LootRoller<'a> {
+    registry: &'a ContentRegistry,
+    context: &'a LootContext,
+}
+
+impl<'a> LootRoller<'a> {
+    pub fn new(registry: &'a ContentRegistry, context: &'a LootContext) -> Self {
+        Self { registry, context }
+    }
+
+    /// Rolls `table_id` once: every guaranteed entry drops, then `rolls`
+    /// weighted draws pick among the rest. Entries whose conditions do not hold
+    /// are left out. Drops of the same item are merged and then split into
+    /// stacks, in the order the items first dropped.
+    pub fn roll(
+        &self,
+        table_id: &str,
+        rng: &mut fastrand::Rng,
+    ) -> Result<Vec<ItemDrop>, LootError> {
+        let mut counts = Vec::new();
+        self.roll_into(table_id, rng, &mut Vec::new(), &mut counts)?;
+        Ok(self.split_stacks(counts))
+    }
+
+    fn roll_into(
+        &self,
+        table_id: &str,
+        rng: &mut fastrand::Rng,
+        path: &mut Vec<String>,
+        counts: &mut Vec<(String, u32)>,
+    ) -> Result<(), LootError> {
+        if let Some(start) = path.iter().position(|id| id == table_id) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(table_id.to_owned());
+            return Err(LootError::Cycle { cycle });
+        }
+        let table = self
+            .registry
+            .loot_tables
+            .get(table_id)
+            .ok_or_else(|| LootError::UnknownTable(table_id.to_owned()))?;
+
+        path.push(table_id.to_owned());
+
+        let eligible: Vec<&LootEntryDef> = table
+            .entries
+            .iter()
+            .filter(|entry| self.context.allows(&entry.conditions))
+            .collect();
+
+        for entry in eligible.iter().filter(|entry| entry.guaranteed) {
+            self.drop_entry(table_id, entry, rng, path, counts)?;
+        }
+
+        let weighted: Vec<&LootEntryDef> = eligible
+            .into_iter()
+            .filter(|entry| !entry.guaranteed && entry.weight > 0)
+            .collect();
+        let total: u64 = weighted.iter().map(|entry| u64::from(entry.weight)).sum();
+        if total > 0 {
+            for _ in 0..table.rolls {
+                let mut pick = rng.u64(0..total);
+                for entry in &weighted {
+                    let weight = u64::from(entry.weight);
+                    if pick < weight {
+                        self.drop_entry(table_id, entry, rng, path, counts)?;
+                        break;
+                    }
+                    pick -= weight;
+                }
+            }
+        }
+
+        path.pop();
+        Ok(())
+    }
+
+    fn drop_entry(
+        &self,
+        table_id: &str,
+        entry: &LootEntryDef,
+        rng: &mut fastrand::Rng,
+        path: &mut Vec<String>,
+        counts: &mut Vec<(String, u32)>,
+    ) -> Result<(), LootError> {
+        let count = rng.u32(entry.min..=entry.max.max(entry.min));
+
+        match (&entry.item, &entry.table) {
+            (Some(item), _) => {
+                if !self.registry.items.contains(item) {
+                    return Err(LootError::UnknownItem {
+                        table: table_id.to_owned(),
+                        item: item.clone(),
+                    });
+                }
+                match counts.iter_mut().find(|(id, _)| id == item) {
+                    Some((_, total)) => *total = total.saturating_add(count),
+                    None => counts.push((item.clone(), count)),
+                }
+            }
+            (None, Some(table)) => {
+                for _ in 0..count {
+                    self.roll_into(table, rng, path, counts)?;
+                }
+            }
+            (None, None) => {}
+        }
+
+        Ok(())
+    }
+
+    fn split_stacks(&self, counts: Vec<(String, u32)>) -> Vec<ItemDrop> {
+        let mut drops = Vec::new();
+        for (item, mut count) in counts {
+            // Items without a Stackable component drop one per stack.
+            let max = self
+                .registry
+                .items
+                .get(&item)
+                .and_then(|def| def.stackable())
+                .map_or(1, |stackable| stackable.max.max(1));
+
+            while count > 0 {
+                let stack = count.min(max);
+                drops.push(ItemDrop {
+                    item: item.clone(),
+                    count: stack,
+                });
+                count -= stack;
+            }
+        }
+        drops
+    }
+}
+
+/// Drop statistics for one item over many rolls of a table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemStats {
+    pub item: String,
+    /// Average number dropped per roll of the table.
+    pub expected_count: f64,
+    /// Fraction of rolls that dropped at least one.
+    pub chance: f64,
+    /// Mean and population variance of the size of each dropped stack.
+    pub stack_mean: f64,
+    pub stack_variance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LootStats {
+    pub table: String,
+    pub rolls: u32,
+    pub seed: u64,
+    /// Every item that dropped at least once, sorted by id.
+    pub items: Vec<ItemStats>,
+}
+
+impl LootStats {
+    pub fn item(&self, item: &str) -> Option<&ItemStats> {
+        self.items.iter().find(|stats| stats.item == item)
+    }
+}
+
+/// Rolls `table_id` `rolls` times from a generator seeded with `seed` and
+/// summarizes the drops. The same inputs always give the same statistics.
+pub fn simulate(
+    roller: &LootRoller<'_>,
+    table_id: &str,
+    rolls: u32,
+    seed: u64,
+) -> Result<LootStats, LootError> {
+    #[derive(Default)]
+    struct Totals {
+        count: u64,
+        rolls_with_drop: u32,
+        stacks: u64,
+        stack_sum: f64,
+        stack_sum_sq: f64,
+    }
+
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let mut totals: BTreeMap<String, Totals> = BTreeMap::new();
+
+    for _ in 0..rolls {
+        let drops = roller.roll(table_id, &mut rng)?;
+
+        let mut dropped_this_roll = BTreeSet::new();
+        for drop in drops {
+            let size = f64::from(drop.count);
+            let item = totals.entry(drop.item.clone()).or_default();
+            item.count += u64::from(drop.count);
+            item.stacks += 1;
+            item.stack_sum += size;
+            item.stack_sum_sq += size * size;
+            dropped_this_roll.insert(drop.item);
+        }
+        for item in dropped_this_roll {
+            totals.entry(item).or_default().rolls_with_drop += 1;
+        }
+    }
+
+    let per_roll = f64::from(rolls.max(1));
+    let items = totals
+        .into_iter()
+        .map(|(item, totals)| {
+            let stacks = totals.stacks.max(1) as f64;
+            let stack_mean = totals.stack_sum / stacks;
+            ItemStats {
+                item,
+                expected_count: totals.count as f64 / per_roll,
+                chance: f64::from(totals.rolls_with_drop) / per_roll,
+                stack_mean,
+                stack_variance: (totals.stack_sum_sq / stacks - stack_mean * stack_mean).max(0.0),
+            }
+        })
+        .collect();
+
+    Ok(LootStats {
+        table: table_id.to_owned(),
+        rolls,
+        seed,
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{loader::parse_data_file, registry::DefSource};
+
+    const ITEMS: &str = r#"
+        Item(id: "bone", name: "Bone", description: "", components: [Stackable(max: 5)]),
+        Item(id: "nail", name: "Nail", description: "", components: [Stackable(max: 50)]),
+        Item(id: "can_suit", name: "Can Suit", description: "", components: []),
+    "#;
+
+    /// A registry holding [`ITEMS`] and the entries in `docs`.
+    fn registry(docs: &str) -> ContentRegistry {
+        let text = format!("[{ITEMS} {docs}]");
+        let mut registry = ContentRegistry::default();
+        for doc in parse_data_file(&text, "test.ron", None)
+            .unwrap()
+            .into_docs()
+        {
+            let source = DefSource {
+                mod_id: "base".to_owned(),
+                file: PathBuf::from("data/loot.ron"),
+            };
+            registry.apply(doc, source).unwrap();
+        }
+        registry
+    }
+
+    fn roll_with(
+        registry: &ContentRegistry,
+        context: &LootContext,
+        table: &str,
+    ) -> Result<Vec<ItemDrop>, LootError> {
+        let mut rng = fastrand::Rng::with_seed(7);
+        LootRoller::new(registry, context).roll(table, &mut rng)
+    }
+
+    fn roll(registry: &ContentRegistry, table: &str) -> Result<Vec<ItemDrop>, LootError> {
+        roll_with(registry, &LootContext::default(), table)
+    }
+
+    fn stack(item: &str, count: u32) -> ItemDrop {
+        ItemDrop {
+            item: item.to_owned(),
+            count,
+        }
+    }
+
+    #[test]
+    fn same_seed_same_drops() {
+        let registry = registry(
+            r#"LootTable(id: "junk", rolls: 10, entries: [
+                (weight: 3, item: "bone", min: 1, max: 4),
+                (weight: 2, item: "nail", min: 1, max: 20),
+                (weight: 1, item: "can_suit"),
+            ])"#,
+        );
+        let context = LootContext::default();
+        let roller = LootRoller::new(&registry, &context);
+
+        let first = roller
+            .roll("junk", &mut fastrand::Rng::with_seed(42))
+            .unwrap();
+        let second = roller
+            .roll("junk", &mut fastrand::Rng::with_seed(42))
+            .unwrap();
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn guaranteed_entries_always_drop() {
+        let registry = registry(
+            r#"LootTable(id: "corpse", rolls: 0, entries: [
+                (item: "bone", min: 2, max: 2, guaranteed: true),
+                (weight: 100, item: "nail"),
+            ])"#,
+        );
+        assert_eq!(roll(&registry, "corpse").unwrap(), [stack("bone", 2)]);
+    }
+
+    #[test]
+    fn nothing_entries_drop_nothing() {
+        let registry = registry(
+            r#"LootTable(id: "empty", rolls: 20, entries: [
+                (weight: 1),
+                (weight: 0, item: "bone"),
+            ])"#,
+        );
+        assert_eq!(roll(&registry, "empty").unwrap(), []);
+    }
+
+    #[test]
+    fn entries_need_their_conditions() {
+        let registry = registry(
+            r#"LootTable(id: "sewer", rolls: 0, entries: [
+                (item: "bone", guaranteed: true, conditions: [ZoneTag("sewer")]),
+                (item: "nail", guaranteed: true, conditions: [MinDifficulty(3), MaxDifficulty(5)]),
+            ])"#,
+        );
+
+        assert_eq!(roll(&registry, "sewer").unwrap(), []);
+
+        let sewer = LootContext {
+            zone_tags: vec!["sewer".to_owned()],
+            difficulty: 3,
+        };
+        assert_eq!(
+            roll_with(&registry, &sewer, "sewer").unwrap(),
+            [stack("bone", 1), stack("nail", 1)]
+        );
+
+        let too_hard = LootContext {
+            difficulty: 6,
+            ..sewer
+        };
+        assert_eq!(
+            roll_with(&registry, &too_hard, "sewer").unwrap(),
+            [stack("bone", 1)]
+        );
+    }
+
+    #[test]
+    fn nested_tables_merge_into_one_drop_list() {
+        let registry = registry(
+            r#"LootTable(id: "outer", rolls: 0, entries: [
+                (item: "nail", guaranteed: true),
+                (table: "inner", min: 3, max: 3, guaranteed: true),
+            ]),
+            LootTable(id: "inner", rolls: 0, entries: [
+                (item: "bone", guaranteed: true),
+                (item: "nail", min: 2, max: 2, guaranteed: true),
+            ])"#,
+        );
+        assert_eq!(
+            roll(&registry, "outer").unwrap(),
+            [stack("nail", 7), stack("bone", 3)]
+        );
+    }
+
+    #[test]
+    fn table_cycles_are_errors() {
+        let registry = registry(
+            r#"LootTable(id: "a", rolls: 0, entries: [(table: "b", guaranteed: true)]),
+            LootTable(id: "b", rolls: 0, entries: [(table: "a", guaranteed: true)])"#,
+        );
+        assert_eq!(
+            roll(&registry, "a").unwrap_err(),
+            LootError::Cycle {
+                cycle: vec!["a".to_owned(), "b".to_owned(), "a".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn drops_are_split_by_stack_size() {
+        let registry = registry(
+            r#"LootTable(id: "hoard", rolls: 0, entries: [
+                (item: "bone", min: 12, max: 12, guaranteed: true),
+                (item: "can_suit", min: 2, max: 2, guaranteed: true),
+            ])"#,
+        );
+        assert_eq!(
+            roll(&registry, "hoard").unwrap(),
+            [
+                stack("bone", 5),
+                stack("bone", 5),
+                stack("bone", 2),
+                stack("can_suit", 1),
+                stack("can_suit", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_tables_and_items_are_errors() {
+        let registry = registry(
+            r#"LootTable(id: "broken", rolls: 0, entries: [(item: "ghost", guaranteed: true)])"#,
+        );
+        assert_eq!(
+            roll(&registry, "missing").unwrap_err(),
+            LootError::UnknownTable("missing".to_owned())
+        );
+        assert_eq!(
+            roll(&registry, "broken").unwrap_err(),
+            LootError::UnknownItem {
+                table: "broken".to_owned(),
+                item: "ghost".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn simulation_matches_the_table_odds() {
+        let registry = registry(
+            r#"LootTable(id: "rat", rolls: 1, entries: [
+                (item: "nail", min: 2, max: 2, guaranteed: true),
+                (weight: 1, item: "bone", min: 1, max: 3),
+                (weight: 3),
+            ])"#,
+        );
+        let context = LootContext::default();
+        let roller = LootRoller::new(&registry, &context);
+        let stats = simulate(&roller, "rat", 20_000, 99).unwrap();
+
+        let nail = stats.item("nail").unwrap();
+        assert_eq!(nail.chance, 1.0);
+        assert_eq!(nail.expected_count, 2.0);
+        assert_eq!(nail.stack_variance, 0.0);
+
+        // A quarter of rolls drop 1-3 bones, two on average.
+        let bone = stats.item("bone").unwrap();
+        assert!((bone.chance - 0.25).abs() < 0.02, "chance {}", bone.chance);
+        assert!(
+            (bone.expected_count - 0.5).abs() < 0.05,
+            "expected count {}",
+            bone.expected_count
+        );
+        assert!(
+            (bone.stack_mean - 2.0).abs() < 0.05,
+            "stack mean {}",
+            bone.stack_mean
+        );
+
+        assert_eq!(simulate(&roller, "rat", 20_000, 99).unwrap(), stats);
+    }
+}
@@ -0,0 +1,572 @@
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use modkit::{
+    error::ParseError,
+    has_comments,
+    hashing::ContentManifest,
+    loader::{MANIFEST_FILE, ModLoader, data_files, parse_data_file},
+    loot::{LootContext, LootRoller, simulate},
+    migrate::{
+        CURRENT_MOD_API_VERSION, check_api_version, migrate_data, migrations_from, set_api_version,
+    },
+    recipe_graph::RecipeGraph,
+    registry::{ContentRegistry, Definition, RegistryEntry},
+    to_pretty_ron,
+    validate::{Severity, validate},
+};
+use serde_json::json;
+
+/// Exit code when the content is invalid or a check did not pass.
+/// Argument errors exit with clap's code 2.
+const EXIT_FAILED: u8 = 1;
+
+#[derive(Parser, Debug)]
+#[command(name = "modkit", about = "Checks, hashes and formats Al-cat-raz mods")]
+struct Cli {
+    /// Directory holding one folder per mod.
+    #[arg(long, global = true, default_value = "mods")]
+    mods_dir: PathBuf,
+
+    /// Only load these mods (plus `base` and their dependencies).
+    #[arg(long = "mod", global = true)]
+    mods: Vec<String>,
+
+    /// Print machine-readable JSON instead of text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Load every mod and check ids, references and values.
+    Validate {
+        /// Fail on warnings as well as errors.
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Print the content hash of every mod, in load order.
+    Hash {
+        /// Also list every file with its size and hash.
+        #[arg(long)]
+        files: bool,
+    },
+    /// List mods in load order and the definitions they provide.
+    List,
+    /// Rewrite data files in the canonical RON layout.
+    Fmt {
+        /// Report files that would change without writing them.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Roll a loot table many times and print drop statistics.
+    LootSim {
+        /// Full id, or the local id if only one mod defines it.
+        table_id: String,
+
+        /// Number of times to roll the table.
+        #[arg(long, default_value_t = 10_000)]
+        rolls: u32,
+
+        /// Seed for the random generator; the same seed gives the same result.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Zone tag for entry conditions (repeatable).
+        #[arg(long = "zone-tag")]
+        zone_tags: Vec<String>,
+
+        /// Difficulty for entry conditions.
+        #[arg(long, default_value_t = 0)]
+        difficulty: u32,
+    },
+    /// Report unobtainable items, recipe cycles and bench tag mismatches.
+    Recipes,
+    /// Print the raw materials and crafting time one unit of an item takes.
+    Cost {
+        /// Full id, or the local id if only one mod defines it.
+        item_id: String,
+    },
+    /// Upgrade mods written for an older `mod_api_version` in place.
+    Migrate {
+        /// Report mods that need migrating without writing them.
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match &cli.command {
+        Command::Validate { deny_warnings } => cmd_validate(&cli, *deny_warnings),
+        Command::Hash { files } => cmd_hash(&cli, *files),
+        Command::List => cmd_list(&cli),
+        Command::Fmt { check } => cmd_fmt(&cli, *check),
+        Command::Migrate { check } => cmd_migrate(&cli, *check),
+        Command::Recipes => cmd_recipes(&cli),
+        Command::Cost { item_id } => cmd_cost(&cli, item_id),
+        Command::LootSim {
+            table_id,
+            rolls,
+            seed,
+            zone_tags,
+            difficulty,
+        } => {
+            let context = LootContext {
+                zone_tags: zone_tags.clone(),
+                difficulty: *difficulty,
+            };
+            cmd_loot_sim(&cli, table_id, *rolls, *seed, &context)
+        }
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_FAILED),
+        Err(err) => {
+            if cli.json {
+                let mut out = json!({ "ok": false, "error": format!("{err:#}") });
+                if let Some(parse_error) = err.downcast_ref::<ParseError>() {
+                    out["parse_error"] = json!(parse_error);
+                }
+                println!("{out}");
+            } else {
+                eprintln!("error: {err:#}");
+            }
+            ExitCode::from(EXIT_FAILED)
+        }
+    }
+}
+
+fn loader(cli: &Cli) -> ModLoader {
+    let loader = ModLoader::new(&cli.mods_dir);
+    if cli.mods.is_empty() {
+        loader
+    } else {
+        loader.with_enabled(cli.mods.clone())
+    }
+}
+
+fn cmd_validate(cli: &Cli, deny_warnings: bool) -> Result<bool> {
+    let registry = loader(cli).load()?;
+    let diagnostics = validate(&registry);
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    let ok = errors == 0 && (!deny_warnings || warnings == 0);
+
+    if cli.json {
+        println!(
+            "{}",
+            json!({
+                "ok": ok,
+                "errors": errors,
+                "warnings": warnings,
+                "diagnostics": diagnostics,
+            })
+        );
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{diagnostic}");
+        }
+        println!(
+            "{} mod(s) checked: {errors} error(s), {warnings} warning(s)",
+            registry.mods.len()
+        );
+    }
+
+    Ok(ok)
+}
+
+fn cmd_hash(cli: &Cli, files: bool) -> Result<bool> {
+    let load_order = loader(cli).resolve()?;
+
+    let mut out = Vec::new();
+    for m in &load_order {
+        let manifest = ContentManifest::from_mod(&m.fs)?;
+        let content_hash = manifest.root_hash();
+
+        if cli.json {
+            let mut entry = json!({
+                "mod_id": m.manifest.mod_id,
+                "version": m.manifest.version,
+                "content_hash": content_hash,
+            });
+            if files {
+                entry["files"] = json!(manifest.files);
+            }
+            out.push(entry);
+            continue;
+        }
+
+        println!(
+            "{} {} {content_hash}",
+            m.manifest.mod_id, m.manifest.version
+        );
+        if files {
+            for file in &manifest.files {
+                println!("  {} {:>8} {}", file.sha256, file.size, file.path);
+            }
+        }
+    }
+
+    if cli.json {
+        println!("{}", serde_json::Value::Array(out));
+    }
+
+    Ok(true)
+}
+
+fn cmd_list(cli: &Cli) -> Result<bool> {
+    let registry = loader(cli).load()?;
+
+    if cli.json {
+        let mods: Vec<_> = registry
+            .mods
+            .iter()
+            .map(|m| {
+                json!({
+                    "mod_id": m.manifest.mod_id,
+                    "name": m.manifest.name,
+                    "version": m.manifest.version,
+                    "dependencies": m.manifest.dependencies,
+                })
+            })
+            .collect();
+
+        println!(
+            "{}",
+            json!({ "mods": mods, "definitions": definitions_json(&registry) })
+        );
+        return Ok(true);
+    }
+
+    for m in &registry.mods {
+        println!(
+            "{} {} - {}",
+            m.manifest.mod_id, m.manifest.version, m.manifest.name
+        );
+    }
+    println!();
+
+    list_defs(registry.items.entries());
+    list_defs(registry.benches.entries());
+    list_defs(registry.recipes.entries());
+    list_defs(registry.loot_tables.entries());
+    list_defs(registry.tags.entries());
+    list_defs(registry.statuses.entries());
+    list_defs(registry.enemies.entries());
+    list_defs(registry.noise_kinds.entries());
+    list_defs(registry.factions.entries());
+    list_defs(registry.dialogues.entries());
+    list_defs(registry.quests.entries());
+
+    Ok(true)
+}
+
+fn list_defs<'a, T: Definition + 'a>(entries: impl Iterator<Item = &'a RegistryEntry<T>>) {
+    for entry in entries {
+        println!(
+            "{:<10} {:<24} {}",
+            T::KIND.to_string(),
+            entry.def.id(),
+            entry.last_touched_by()
+        );
+    }
+}
+
+fn definitions_json(registry: &ContentRegistry) -> Vec<serde_json::Value> {
+    fn push<'a, T: Definition + 'a>(
+        out: &mut Vec<serde_json::Value>,
+        entries: impl Iterator<Item = &'a RegistryEntry<T>>,
+    ) {
+        for entry in entries {
+            let defined_by = entry.defined_by();
+            let last = entry.last_touched_by();
+            out.push(json!({
+                "kind": T::KIND,
+                "id": entry.def.id(),
+                "mod_id": defined_by.mod_id,
+                "file": defined_by.file,
+                "last_touched_by": last.mod_id,
+            }));
+        }
+    }
+
+    let mut out = Vec::new();
+    push(&mut out, registry.items.entries());
+    push(&mut out, registry.benches.entries());
+    push(&mut out, registry.recipes.entries());
+    push(&mut out, registry.loot_tables.entries());
+    push(&mut out, registry.tags.entries());
+    push(&mut out, registry.statuses.entries());
+    push(&mut out, registry.enemies.entries());
+    push(&mut out, registry.noise_kinds.entries());
+    push(&mut out, registry.factions.entries());
+    push(&mut out, registry.dialogues.entries());
+    push(&mut out, registry.quests.entries());
+    out
+}
+
+fn cmd_fmt(cli: &Cli, check: bool) -> Result<bool> {
+    let mut changed = Vec::new();
+    let mut skipped = Vec::new();
+
+    for discovered in loader(cli).resolve()? {
+        let Some(root) = discovered.fs.dir() else {
+            skipped.push((discovered.fs.root().to_path_buf(), "zipped mod"));
+            continue;
+        };
+        if discovered.manifest.mod_api_version != CURRENT_MOD_API_VERSION {
+            skipped.push((root.to_path_buf(), "run `modkit migrate` first"));
+            continue;
+        }
+
+        for rel in data_files(&discovered.fs)? {
+            let path = root.join(rel);
+            let text =
+                fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+
+            if has_comments(&text) {
+                skipped.push((path, "contains comments"));
+                continue;
+            }
+
+            let file = parse_data_file(&text, &path, Some(&discovered.manifest.mod_id))?;
+            let formatted = to_pretty_ron(&file, &text)
+                .with_context(|| format!("formatting {}", path.display()))?;
+            if formatted == text {
+                continue;
+            }
+
+            if !check {
+                fs::write(&path, formatted)
+                    .with_context(|| format!("writing {}", path.display()))?;
+            }
+            changed.push(path);
+        }
+    }
+
+    if cli.json {
+        println!(
+            "{}",
+            json!({
+                "ok": !check || changed.is_empty(),
+                "changed": changed,
+                "skipped": skipped.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            })
+        );
+    } else {
+        let verb = if check { "would reformat" } else { "formatted" };
+        for path in &changed {
+            println!("{verb} {}", path.display());
+        }
+        for (path, reason) in &skipped {
+            println!("skipped {} ({reason})", path.display());
+        }
+    }
+
+    Ok(!check || changed.is_empty())
+}
+
+fn cmd_migrate(cli: &Cli, check: bool) -> Result<bool> {
+    let mut migrated = Vec::new();
+    let mut skipped = Vec::new();
+
+    for discovered in loader(cli).resolve()? {
+        let manifest = &discovered.manifest;
+        check_api_version(manifest)?;
+        let from = manifest.mod_api_version;
+        if from == CURRENT_MOD_API_VERSION {
+            continue;
+        }
+
+        let fs = &discovered.fs;
+        let Some(root) = fs.dir() else {
+            skipped.push(fs.root().to_path_buf());
+            continue;
+        };
+
+        let mut changed = Vec::new();
+        for rel in data_files(fs)? {
+            let text = fs.read_to_string(&rel)?;
+            let upgraded = migrate_data(&text, from);
+            if upgraded == text {
+                continue;
+            }
+
+            let path = root.join(rel);
+            if !check {
+                fs::write(&path, upgraded)
+                    .with_context(|| format!("writing {}", path.display()))?;
+            }
+            changed.push(path);
+        }
+
+        // The manifest goes last so an interrupted run is simply run again.
+        let manifest_path = root.join(MANIFEST_FILE);
+        if !check {
+            let text = fs.read_to_string(MANIFEST_FILE)?;
+            let updated = set_api_version(&text, CURRENT_MOD_API_VERSION).with_context(|| {
+                format!("{} has no mod_api_version line", manifest_path.display())
+            })?;
+            fs::write(&manifest_path, updated)
+                .with_context(|| format!("writing {}", manifest_path.display()))?;
+        }
+
+        migrated.push((manifest.mod_id.clone(), from, changed));
+    }
+
+    // Zipped mods still need migrating after a real run too, but they load
+    // fine as the loader migrates them in memory.
+    let ok = !check || (migrated.is_empty() && skipped.is_empty());
+    if cli.json {
+        let mods: Vec<_> = migrated
+            .iter()
+            .map(|(mod_id, from, changed)| {
+                json!({
+                    "mod_id": mod_id,
+                    "from": from,
+                    "to": CURRENT_MOD_API_VERSION,
+                    "changed": changed,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            json!({ "ok": ok, "migrated": mods, "skipped": skipped })
+        );
+    } else {
+        let verb = if check { "would migrate" } else { "migrated" };
+        for (mod_id, from, changed) in &migrated {
+            println!("{verb} {mod_id} from {from} to {CURRENT_MOD_API_VERSION}");
+            for migration in migrations_from(*from) {
+                println!("  - {}", migration.summary);
+            }
+            for path in changed {
+                println!("  {}", path.display());
+            }
+        }
+        for path in &skipped {
+            println!(
+                "skipped {} (zipped mods cannot be rewritten)",
+                path.display()
+            );
+        }
+    }
+
+    Ok(ok)
+}
+
+fn cmd_loot_sim(
+    cli: &Cli,
+    table_id: &str,
+    rolls: u32,
+    seed: u64,
+    context: &LootContext,
+) -> Result<bool> {
+    let registry = loader(cli).load()?;
+    let table_id = registry
+        .loot_tables
+        .qualified_id(table_id)
+        .unwrap_or(table_id);
+    let stats = simulate(&LootRoller::new(&registry, context), table_id, rolls, seed)?;
+
+    if cli.json {
+        println!("{}", json!(stats));
+        return Ok(true);
+    }
+
+    println!("{table_id}: {rolls} roll(s), seed {seed}");
+    println!(
+        "{:<24} {:>10} {:>8} {:>11} {:>10}",
+        "item", "per roll", "chance", "stack mean", "stack var"
+    );
+    for item in &stats.items {
+        println!(
+            "{:<24} {:>10.3} {:>7.2}% {:>11.2} {:>10.2}",
+            item.item,
+            item.expected_count,
+            item.chance * 100.0,
+            item.stack_mean,
+            item.stack_variance
+        );
+    }
+
+    Ok(true)
+}
+
+fn cmd_recipes(cli: &Cli) -> Result<bool> {
+    let registry = loader(cli).load()?;
+    let graph = RecipeGraph::new(&registry);
+    let unobtainable = graph.unobtainable_items();
+    let cycles = graph.cycles();
+    let mismatches = graph.bench_mismatches();
+    // Unobtainable items may be granted by scripts or quests, so only cycles
+    // and mismatches fail the check.
+    let ok = cycles.is_empty() && mismatches.is_empty();
+
+    if cli.json {
+        println!(
+            "{}",
+            json!({
+                "ok": ok,
+                "unobtainable": unobtainable,
+                "cycles": cycles,
+                "bench_mismatches": mismatches,
+            })
+        );
+        return Ok(ok);
+    }
+
+    println!("unobtainable items: {}", unobtainable.len());
+    for item in &unobtainable {
+        println!("  {item}");
+    }
+    println!("recipe cycles: {}", cycles.len());
+    for cycle in &cycles {
+        println!("  {}", cycle.join(", "));
+    }
+    println!("bench tag mismatches: {}", mismatches.len());
+    for mismatch in &mismatches {
+        println!(
+            "  recipe '{}' at bench '{}': {}",
+            mismatch.recipe,
+            mismatch.bench,
+            mismatch.disallowed_tags.join(", ")
+        );
+    }
+
+    Ok(ok)
+}
+
+fn cmd_cost(cli: &Cli, item_id: &str) -> Result<bool> {
+    let registry = loader(cli).load()?;
+    let item_id = registry.items.qualified_id(item_id).unwrap_or(item_id);
+    let cost = RecipeGraph::new(&registry)
+        .cost(item_id)
+        .with_context(|| format!("unknown item '{item_id}'"))?;
+
+    if cli.json {
+        println!("{}", json!(cost));
+        return Ok(true);
+    }
+
+    println!("{item_id}: {:.2}s crafting", cost.time_s);
+    for (item, count) in &cost.raw {
+        println!("  {count:>8.2} x {item}");
+    }
+    if !cost.recipes.is_empty() {
+        println!("recipes: {}", cost.recipes.join(", "));
+    }
+
+    Ok(true)
+}
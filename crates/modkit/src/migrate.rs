@@ -0,0 +1,322 @@
+use thiserror::Error;
+
+use crate::manifest::ModManifest;
+
+/// The `mod_api_version` this build's schema is written for.
+pub const CURRENT_MOD_API_VERSION: u32 = 2;
+
+/// The oldest `mod_api_version` that can still be migrated on load.
+pub const MIN_MOD_API_VERSION: u32 = 1;
+
+/// Upgrades data files written for one API version to the next.
+///
+/// Migrations work on the RON text rather than parsed values, so running
+/// them in place keeps comments and layout intact.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Data written for this version is upgraded to `from + 1`.
+    pub from: u32,
+    pub summary: &'static str,
+    pub migrate: fn(&str) -> String,
+}
+
+/// Every registered migration, oldest first.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    summary: "gadgets list their slots as `slots: [..]` instead of `slot:` or a single value",
+    migrate: gadget_slot_to_slots,
+}];
+
+#[derive(Debug, Error)]
+#[error(
+    "mod '{mod_id}' targets mod_api_version {found}, but this build supports versions {} to {}",
+    MIN_MOD_API_VERSION,
+    CURRENT_MOD_API_VERSION
+)]
+pub struct UnsupportedApiVersion {
+    pub mod_id: String,
+    pub found: u32,
+}
+
+pub fn check_api_version(manifest: &ModManifest) -> Result<(), UnsupportedApiVersion> {
+    let found = manifest.mod_api_version;
+    if (MIN_MOD_API_VERSION..=CURRENT_MOD_API_VERSION).contains(&found) {
+        Ok(())
+    } else {
+        Err(UnsupportedApiVersion {
+            mod_id: manifest.mod_id.clone(),
+            found,
+        })
+    }
+}
+
+/// The migrations that upgrade data written for `from` to the current version.
+pub fn migrations_from(from: u32) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS.iter().filter(move |m| m.from >= from)
+}
+
+/// Upgrades the text of a data file written for `from` to the current version.
+pub fn migrate_data(text: &str, from: u32) -> String {
+    migrations_from(from).fold(text.to_owned(), |text, m| (m.migrate)(&text))
+}
+
+/// Rewrites the `mod_api_version` line of a `mod.toml`, keeping everything
+/// else as written. Returns `None` if the manifest has no such line.
+pub fn set_api_version(manifest_text: &str, version: u32) -> Option<String> {
+    let mut found = false;
+    let mut out = String::with_capacity(manifest_text.len());
+    for line in manifest_text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let is_version_line = trimmed
+            .strip_prefix("mod_api_version")
+            .is_some_and(|rest| rest.trim_start().starts_with('='));
+
+        if is_version_line && !found {
+            found = true;
+            let indent = &line[..line.len() - trimmed.len()];
+            let newline = &line[line.trim_end_matches(['\r', '\n']).len()..];
+            out.push_str(&format!("{indent}mod_api_version = {version}{newline}"));
+        } else {
+            out.push_str(line);
+        }
+    }
+
+    found.then_some(out)
+}
+
+/// v1 -> v2: `Gadget(slot: Edge, ..)` becomes `Gadget(slots: [Edge], ..)`.
+///
+/// v1 accepted one slot or a list under either name, so `slot: [Edge, Handle]`
+/// is only renamed and `slots: Edge` is only wrapped. `GadgetHost` keeps its single `slot:`; only fields directly inside a
+/// `Gadget(..)`, `GadgetDef(..)` or an unnamed struct wrapped by one are renamed.
+fn gadget_slot_to_slots(text: &str) -> String {
+    struct Frame {
+        gadget: bool,
+        empty: bool,
+    }
+
+    let bytes = text.as_bytes();
+    let mut edits: Vec<(usize, usize, &str)> = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut prev_ident: Option<&str> = None;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if let Some(end) = skip_comment(bytes, i) {
+            i = end;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let starts_gadget = match (c, prev_ident) {
+            (b'(', Some(ident)) => ident == "Gadget" || ident == "GadgetDef",
+            (b'(', None) => frames.last().is_some_and(|f| f.gadget && f.empty),
+            _ => false,
+        };
+        if let Some(frame) = frames.last_mut() {
+            frame.empty = false;
+        }
+        prev_ident = None;
+
+        match c {
+            b'"' => i = skip_string(bytes, i),
+            b'(' | b'[' | b'{' => {
+                frames.push(Frame {
+                    gadget: starts_gadget,
+                    empty: true,
+                });
+                i += 1;
+            }
+            b')' | b']' | b'}' => {
+                frames.pop();
+                i += 1;
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let ident = &text[start..i];
+
+                let colon = skip_trivia(bytes, i);
+                if (ident == "slot" || ident == "slots")
+                    && frames.last().is_some_and(|f| f.gadget)
+                    && bytes.get(colon) == Some(&b':')
+                {
+                    if ident == "slot" {
+                        edits.push((start, i, "slots"));
+                    }
+                    let value_start = skip_trivia(bytes, colon + 1);
+                    if bytes.get(value_start) != Some(&b'[') {
+                        let value_end = value_end(bytes, value_start);
+                        edits.push((value_start, value_start, "["));
+                        edits.push((value_end, value_end, "]"));
+                    }
+                }
+                prev_ident = Some(ident);
+            }
+            _ => i += 1,
+        }
+    }
+
+    let mut out = String::with_capacity(text.len() + edits.len());
+    let mut pos = 0;
+    for (start, end, replacement) in edits {
+        out.push_str(&text[pos..start]);
+        out.push_str(replacement);
+        pos = end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// End of the comment starting at `i`, if one does.
+fn skip_comment(bytes: &[u8], i: usize) -> Option<usize> {
+    match bytes.get(i..i + 2)? {
+        b"//" => Some(
+            bytes[i..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |n| i + n),
+        ),
+        b"/*" => Some(
+            bytes[i + 2..]
+                .windows(2)
+                .position(|w| w == b"*/")
+                .map_or(bytes.len(), |n| i + 2 + n + 2),
+        ),
+        _ => None,
+    }
+}
+
+/// End of the string literal whose opening quote is at `i`.
+fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn skip_trivia(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        if let Some(end) = skip_comment(bytes, i) {
+            i = end;
+        } else if bytes[i].is_ascii_whitespace() {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+/// End of the value starting at `i`: just past its last significant byte
+/// before the `,` or closing bracket that ends the field.
+fn value_end(bytes: &[u8], mut i: usize) -> usize {
+    let mut depth = 0usize;
+    let mut end = i;
+    while i < bytes.len() {
+        if let Some(next) = skip_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b',' if depth == 0 => break,
+            b')' | b']' | b'}' if depth == 0 => break,
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'"' => {
+                i = skip_string(bytes, i);
+                end = i;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+        end = i;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::parse_data_file;
+
+    fn item(components: &str) -> String {
+        format!(r#"Item(id: "a", name: "A", description: "", components: [{components}])"#)
+    }
+
+    #[test]
+    fn gadget_slot_becomes_a_list() {
+        let migrated = migrate_data(&item("Gadget(slot: Edge, modifiers: [])"), 1);
+        assert_eq!(migrated, item("Gadget(slots: [Edge], modifiers: [])"));
+        assert!(parse_data_file(&migrated, "a.ron", None).is_ok());
+    }
+
+    #[test]
+    fn slot_lists_are_only_renamed() {
+        let migrated = migrate_data(&item("Gadget(slot: [Edge, Handle])"), 1);
+        assert_eq!(migrated, item("Gadget(slots: [Edge, Handle])"));
+        assert!(parse_data_file(&migrated, "a.ron", None).is_ok());
+    }
+
+    #[test]
+    fn single_slots_value_is_wrapped() {
+        let migrated = migrate_data(&item("Gadget(slots: Edge, modifiers: [])"), 1);
+        assert_eq!(migrated, item("Gadget(slots: [Edge], modifiers: [])"));
+        assert!(parse_data_file(&migrated, "a.ron", None).is_ok());
+    }
+
+    #[test]
+    fn gadget_host_keeps_its_slot() {
+        let text = item("GadgetHost(slot: Edge, count: 1)");
+        assert_eq!(migrate_data(&text, 1), text);
+    }
+
+    #[test]
+    fn trailing_comment_stays_outside_the_list() {
+        let text = item("Gadget(\n    slot: Edge, // the sharp end\n    modifiers: [],\n)");
+        assert_eq!(
+            migrate_data(&text, 1),
+            item("Gadget(\n    slots: [Edge], // the sharp end\n    modifiers: [],\n)")
+        );
+
+        let text = item("Gadget(modifiers: [], slot: Edge /* last */)");
+        assert_eq!(
+            migrate_data(&text, 1),
+            item("Gadget(modifiers: [], slots: [Edge] /* last */)")
+        );
+    }
+
+    #[test]
+    fn slot_after_modifiers_is_renamed() {
+        let text = item("Gadget(modifiers: [StatModifier(stat: Damage, factor: 1.5)], slot: Edge)");
+        let migrated = migrate_data(&text, 1);
+        assert_eq!(
+            migrated,
+            item("Gadget(modifiers: [StatModifier(stat: Damage, factor: 1.5)], slots: [Edge])")
+        );
+        assert!(parse_data_file(&migrated, "a.ron", None).is_ok());
+    }
+
+    #[test]
+    fn current_version_data_is_left_alone() {
+        let text = item("Gadget(slots: [Edge], modifiers: []), GadgetHost(slot: Edge, count: 1)");
+        assert_eq!(migrate_data(&text, CURRENT_MOD_API_VERSION), text);
+        assert_eq!(gadget_slot_to_slots(&text), text);
+    }
+}
@@ -0,0 +1,179 @@
+/// The script function that emits a noise: `emit_noise(x, y, loudness, kind)`.
+pub const EMIT_NOISE: &str = "emit_noise";
+
+/// An `emit_noise` call in a script whose kind is a string literal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoiseCall {
+    /// 1-based line of the call.
+    pub line: usize,
+    pub kind: String,
+}
+
+/// Every `emit_noise` call in a Rhai script that passes its kind as a string
+/// literal, in source order. Calls building the kind at runtime are skipped,
+/// as are calls inside comments and strings.
+pub fn emit_noise_calls(source: &str) -> Vec<NoiseCall> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut calls = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\n' => line += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    line += usize::from(chars[i] == '\n');
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            '"' | '`' => {
+                let end = string_end(&chars, i);
+                line += chars[i..end].iter().filter(|&&c| c == '\n').count();
+                i = end;
+                continue;
+            }
+            c if is_ident_char(c) => {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                if ident == EMIT_NOISE
+                    && let Some(args) = call_args(&chars, i)
+                    && let Some(kind) = args.get(3).and_then(|arg| string_literal(arg))
+                {
+                    calls.push(NoiseCall { line, kind });
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    calls
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Index just past the string starting at `start`.
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// The top-level arguments of a call whose name ends at `after_name`, or
+/// `None` if no `(` follows.
+fn call_args(chars: &[char], after_name: usize) -> Option<Vec<String>> {
+    let mut i = after_name;
+    while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+        i += 1;
+    }
+    if chars.get(i) != Some(&'(') {
+        return None;
+    }
+    i += 1;
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '"' | '`' => {
+                let end = string_end(chars, i);
+                current.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => {
+                args.push(current);
+                return Some(args);
+            }
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(std::mem::take(&mut current));
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        current.push(chars[i]);
+        i += 1;
+    }
+    None
+}
+
+/// The text of `arg` if it is a single plain string literal.
+fn string_literal(arg: &str) -> Option<String> {
+    let inner = arg.trim().strip_prefix('"')?.strip_suffix('"')?;
+    (!inner.contains(['"', '\\'])).then(|| inner.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(line: usize, kind: &str) -> NoiseCall {
+        NoiseCall {
+            line,
+            kind: kind.to_owned(),
+        }
+    }
+
+    #[test]
+    fn literal_kinds_are_found_with_their_line() {
+        let source = "let x = 1;\nemit_noise(x, y, 0.5, \"thud\");\n\n  emit_noise (x, y, f(1, 2), \"scrape\")\n";
+        assert_eq!(
+            emit_noise_calls(source),
+            [call(2, "thud"), call(4, "scrape")]
+        );
+    }
+
+    #[test]
+    fn calls_in_comments_and_strings_are_skipped() {
+        let source = r#"// emit_noise(x, y, 1.0, "line");
+/* emit_noise(x, y, 1.0, "block") */
+let s = "emit_noise(x, y, 1.0, \"string\")";
+let t = `emit_noise(x, y, 1.0, "template")`;
+emit_noise(x, y, 1.0, "real");
+"#;
+        assert_eq!(emit_noise_calls(source), [call(5, "real")]);
+    }
+
+    #[test]
+    fn kinds_built_at_runtime_are_skipped() {
+        let source = r#"emit_noise(x, y, 1.0, kind);
+emit_noise(x, y, 1.0, "wet_" + suffix);
+emit_noise(x, y, 1.0, `${kind}`);
+emit_noise(x, y, 1.0);
+"#;
+        assert!(emit_noise_calls(source).is_empty());
+    }
+
+    #[test]
+    fn lines_inside_block_comments_and_strings_are_counted() {
+        let source = "/* one\ntwo\nthree */ let s = \"a\nb\";\nemit_noise(x, y, 1.0, \"after\");";
+        assert_eq!(emit_noise_calls(source), [call(5, "after")]);
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{registry::ContentRegistry, schema::recipes::RecipeDef, tags::disallowed_tags};
+
+/// Items linked by the recipes that turn ingredients into outputs.
+#[derive(Debug, Clone)]
+pub struct RecipeGraph<'a> {
+    registry: &'a ContentRegistry,
+    /// Recipes producing each item, sorted by recipe id.
+    producers: BTreeMap<&'a str, Vec<&'a RecipeDef>>,
+}
+
+/// A recipe whose `recipe_tags` its bench does not allow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BenchMismatch {
+    pub recipe: String,
+    pub bench: String,
+    pub disallowed_tags: Vec<String>,
+}
+
+/// What one unit of an item costs to craft from scratch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CraftCost {
+    pub item: String,
+    /// Raw materials needed per unit: items no recipe produces, or whose
+    /// recipes all lead back into the item being costed. Counts are
+    /// fractional when a recipe outputs more than one unit.
+    pub raw: BTreeMap<String, f64>,
+    /// Seconds of crafting per unit, each recipe's `time_s` divided by its
+    /// bench's `craft_speed_mult`.
+    pub time_s: f64,
+    /// Every recipe used, in the order they were expanded.
+    pub recipes: Vec<String>,
+}
+
+impl<'a> RecipeGraph<'a> {
+    pub fn new(registry: &'a ContentRegistry) -> Self {
+        let mut producers: BTreeMap<&str, Vec<&RecipeDef>> = BTreeMap::new();
+        for recipe in registry.recipes.iter() {
+            for output in &recipe.outputs {
+                let recipes = producers.entry(output.item.as_str()).or_default();
+                if !recipes.iter().any(|r| r.id == recipe.id) {
+                    recipes.push(recipe);
+                }
+            }
+        }
+
+        Self {
+            registry,
+            producers,
+        }
+    }
+
+    pub fn producers(&self, item: &str) -> &[&'a RecipeDef] {
+        self.producers.get(item).map_or(&[], Vec::as_slice)
+    }
+
+    /// Items that some loot table drops, or that a recipe crafts from
+    /// ingredients which are themselves obtainable.
+    pub fn obtainable_items(&self) -> BTreeSet<&'a str> {
+        let mut obtainable: BTreeSet<&str> = self
+            .registry
+            .loot_tables
+            .iter()
+            .flat_map(|table| &table.entries)
+            .filter_map(|entry| entry.item.as_deref())
+            .collect();
+
+        loop {
+            let mut changed = false;
+            for recipe in self.registry.recipes.iter() {
+                let craftable = recipe
+                    .ingredients
+                    .iter()
+                    .all(|stack| obtainable.contains(stack.item.as_str()));
+                if craftable {
+                    for output in &recipe.outputs {
+                        changed |= obtainable.insert(output.item.as_str());
+                    }
+                }
+            }
+            if !changed {
+                return obtainable;
+            }
+        }
+    }
+
+    /// Defined items that can never be obtained, sorted by id.
+    pub fn unobtainable_items(&self) -> Vec<&'a str> {
+        let obtainable = self.obtainable_items();
+        self.registry
+            .items
+            .ids()
+            .filter(|id| !obtainable.contains(id))
+            .collect()
+    }
+
+    /// Groups of items that can each be crafted, directly or indirectly, from
+    /// one another. Each group is sorted by id; groups are sorted by their
+    /// first item.
+    pub fn cycles(&self) -> Vec<Vec<&'a str>> {
+        // Edges run from each ingredient to every output of the same recipe.
+        let mut edges: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for recipe in self.registry.recipes.iter() {
+            for ingredient in &recipe.ingredients {
+                for output in &recipe.outputs {
+                    edges
+                        .entry(ingredient.item.as_str())
+                        .or_default()
+                        .insert(output.item.as_str());
+                }
+            }
+        }
+
+        let mut cycles = Vec::new();
+        let mut assigned = BTreeSet::new();
+        for &start in edges.keys() {
+            if assigned.contains(start) {
+                continue;
+            }
+            let forward = reachable(&edges, start);
+            if !forward.contains(start) {
+                continue;
+            }
+            let group: Vec<&str> = forward
+                .into_iter()
+                .filter(|&item| reachable(&edges, item).contains(start))
+                .collect();
+            assigned.extend(group.iter().copied());
+            cycles.push(group);
+        }
+
+        cycles.sort();
+        cycles
+    }
+
+    /// Recipes whose bench exists but does not allow all of their tags.
+    pub fn bench_mismatches(&self) -> Vec<BenchMismatch> {
+        self.registry
+            .recipes
+            .iter()
+            .filter_map(|recipe| {
+                let bench = self.registry.benches.get(&recipe.bench_id)?;
+                let disallowed = disallowed_tags(self.registry, bench, recipe);
+                (!disallowed.is_empty()).then(|| BenchMismatch {
+                    recipe: recipe.id.clone(),
+                    bench: bench.id.clone(),
+                    disallowed_tags: disallowed.into_iter().map(str::to_owned).collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Breaks one unit of `item` down to raw materials, using the first
+    /// producing recipe (by id) that does not lead back to an item already
+    /// being expanded. Returns `None` for unknown items.
+    pub fn cost(&self, item: &str) -> Option<CraftCost> {
+        if !self.registry.items.contains(item) {
+            return None;
+        }
+
+        let mut cost = CraftCost {
+            item: item.to_owned(),
+            raw: BTreeMap::new(),
+            time_s: 0.0,
+            recipes: Vec::new(),
+        };
+        self.expand(item, 1.0, &mut Vec::new(), &mut cost);
+        Some(cost)
+    }
+
+    fn expand<'s>(
+        &'s self,
+        item: &'s str,
+        amount: f64,
+        path: &mut Vec<&'s str>,
+        cost: &mut CraftCost,
+    ) {
+        let recipe = self.producers(item).iter().find(|recipe| {
+            !recipe
+                .ingredients
+                .iter()
+                .any(|stack| stack.item == item || path.contains(&stack.item.as_str()))
+        });
+
+        let Some(recipe) = recipe else {
+            *cost.raw.entry(item.to_owned()).or_default() += amount;
+            return;
+        };
+
+        let produced: u32 = recipe
+            .outputs
+            .iter()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum();
+        let crafts = amount / f64::from(produced.max(1));
+
+        let speed = self
+            .registry
+            .benches
+            .get(&recipe.bench_id)
+            .map(|bench| bench.craft_speed_mult)
+            .filter(|&mult| mult > 0.0)
+            .unwrap_or(1.0);
+        cost.time_s += crafts * f64::from(recipe.time_s) / f64::from(speed);
+        cost.recipes.push(recipe.id.clone());
+
+        path.push(item);
+        for stack in &recipe.ingredients {
+            self.expand(&stack.item, crafts * f64::from(stack.count), path, cost);
+        }
+        path.pop();
+    }
+}
+
+fn reachable<'a>(
+    edges: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    start: &'a str,
+) -> BTreeSet<&'a str> {
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![start];
+    while let Some(current) = stack.pop() {
+        for &next in edges.get(current).into_iter().flatten() {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{loader::parse_data_file, registry::DefSource};
+
+    const CONTENT: &str = r#"
+        Item(id: "ore", name: "Ore", description: "", components: []),
+        Item(id: "ingot", name: "Ingot", description: "", components: []),
+        Item(id: "plate", name: "Plate", description: "", components: []),
+        Bench(id: "forge", name: "Forge", craft_speed_mult: 2, noise_mult: 1, allowed_recipe_tags: ["metal"]),
+        Bench(id: "anvil", name: "Anvil", craft_speed_mult: 1, noise_mult: 1),
+        LootTable(id: "mine", rolls: 1, entries: [(item: "ore")]),
+        Recipe(
+            id: "smelt",
+            name: "",
+            bench_id: "forge",
+            time_s: 4,
+            recipe_tags: ["metal"],
+            ingredients: [(item: "ore", count: 3)],
+            outputs: [(item: "ingot", count: 2)],
+        ),
+        Recipe(
+            id: "hammer",
+            name: "",
+            bench_id: "anvil",
+            time_s: 2,
+            ingredients: [(item: "ingot", count: 1)],
+            outputs: [(item: "plate", count: 1)],
+        ),
+    "#;
+
+    /// A registry holding [`CONTENT`] and the entries in `docs`.
+    fn registry(docs: &str) -> ContentRegistry {
+        let text = format!("[{CONTENT} {docs}]");
+        let mut registry = ContentRegistry::default();
+        for doc in parse_data_file(&text, "test.ron", None)
+            .unwrap()
+            .into_docs()
+        {
+            let source = DefSource {
+                mod_id: "base".to_owned(),
+                file: PathBuf::from("data/recipes.ron"),
+            };
+            registry.apply(doc, source).unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn cost_divides_by_output_count_and_bench_speed() {
+        let registry = registry("");
+        let cost = RecipeGraph::new(&registry).cost("plate").unwrap();
+
+        // Half a smelt (3 ore, 4s at double speed) per ingot, then one hammer.
+        assert_eq!(cost.raw, BTreeMap::from([("ore".to_owned(), 1.5)]));
+        assert_eq!(cost.time_s, 0.5 * 4.0 / 2.0 + 2.0);
+        assert_eq!(cost.recipes, ["hammer", "smelt"]);
+        assert_eq!(RecipeGraph::new(&registry).cost("gold"), None);
+    }
+
+    #[test]
+    fn cost_stops_at_recipes_leading_back_to_the_item() {
+        let registry = registry(
+            r#"Recipe(
+                id: "recast",
+                name: "",
+                bench_id: "forge",
+                time_s: 1,
+                ingredients: [(item: "plate", count: 1)],
+                outputs: [(item: "ore", count: 1)],
+            )"#,
+        );
+        let cost = RecipeGraph::new(&registry).cost("plate").unwrap();
+        assert_eq!(cost.raw, BTreeMap::from([("ore".to_owned(), 1.5)]));
+    }
+
+    #[test]
+    fn cycles_group_items_made_from_each_other() {
+        assert!(RecipeGraph::new(&registry("")).cycles().is_empty());
+
+        let registry = registry(
+            r#"Recipe(
+                id: "recast",
+                name: "",
+                bench_id: "forge",
+                time_s: 1,
+                ingredients: [(item: "plate", count: 1)],
+                outputs: [(item: "ore", count: 1)],
+            )"#,
+        );
+        assert_eq!(
+            RecipeGraph::new(&registry).cycles(),
+            [["ingot", "ore", "plate"]]
+        );
+    }
+
+    #[test]
+    fn a_recipe_using_its_own_output_is_a_cycle() {
+        let registry = registry(
+            r#"Recipe(
+                id: "polish",
+                name: "",
+                bench_id: "anvil",
+                time_s: 1,
+                ingredients: [(item: "plate", count: 1), (item: "ore", count: 1)],
+                outputs: [(item: "plate", count: 1)],
+            )"#,
+        );
+        assert_eq!(RecipeGraph::new(&registry).cycles(), [["plate"]]);
+    }
+
+    #[test]
+    fn items_without_a_source_are_unobtainable() {
+        let registry = registry(
+            r#"
+            Item(id: "gem", name: "Gem", description: "", components: []),
+            Item(id: "ring", name: "Ring", description: "", components: []),
+            Recipe(
+                id: "set",
+                name: "",
+                bench_id: "anvil",
+                time_s: 1,
+                ingredients: [(item: "gem", count: 1), (item: "plate", count: 1)],
+                outputs: [(item: "ring", count: 1)],
+            ),
+            "#,
+        );
+        let graph = RecipeGraph::new(&registry);
+        assert_eq!(graph.unobtainable_items(), ["gem", "ring"]);
+        assert!(graph.obtainable_items().contains("plate"));
+    }
+
+    #[test]
+    fn recipes_with_tags_their_bench_disallows_are_mismatches() {
+        let registry = registry(
+            r#"Recipe(
+                id: "whittle",
+                name: "",
+                bench_id: "forge",
+                time_s: 1,
+                recipe_tags: ["wood", "metal"],
+                ingredients: [],
+                outputs: [(item: "ore", count: 1)],
+            )"#,
+        );
+        assert_eq!(
+            RecipeGraph::new(&registry).bench_mismatches(),
+            [BenchMismatch {
+                recipe: "whittle".to_owned(),
+                bench: "forge".to_owned(),
+                disallowed_tags: vec!["wood".to_owned()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,757 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::PathBuf,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    ids::{local_id, split_id},
+    lang::Translations,
+    loader::BASE_MOD_ID,
+    manifest::ModManifest,
+    schema::{
+        benches::BenchDef,
+        dialogue::DialogueDef,
+        document::{ContentDoc, DefRef},
+        enemies::EnemyDef,
+        factions::FactionDef,
+        items::{BUILTIN_COMPONENT_RULES, ComponentRule, ItemDef},
+        loot::LootTableDef,
+        noise::NoiseKindDef,
+        patches::{Patch, PatchDef},
+        quests::QuestDef,
+        recipes::RecipeDef,
+        status::StatusDef,
+        tags::TagDef,
+    },
+    vfs::ModFs,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefKind {
+    Item,
+    Bench,
+    Recipe,
+    LootTable,
+    Tag,
+    Status,
+    Enemy,
+    NoiseKind,
+    Faction,
+    Dialogue,
+    Quest,
+}
+
+impl DefKind {
+    pub const ALL: [DefKind; 11] = [
+        DefKind::Item,
+        DefKind::Bench,
+        DefKind::Recipe,
+        DefKind::LootTable,
+        DefKind::Tag,
+        DefKind::Status,
+        DefKind::Enemy,
+        DefKind::NoiseKind,
+        DefKind::Faction,
+        DefKind::Dialogue,
+        DefKind::Quest,
+    ];
+}
+
+impl fmt::Display for DefKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DefKind::Item => "item",
+            DefKind::Bench => "bench",
+            DefKind::Recipe => "recipe",
+            DefKind::LootTable => "loot table",
+            DefKind::Tag => "tag",
+            DefKind::Status => "status",
+            DefKind::Enemy => "enemy",
+            DefKind::NoiseKind => "noise kind",
+            DefKind::Faction => "faction",
+            DefKind::Dialogue => "dialogue",
+            DefKind::Quest => "quest",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Implemented by every top-level definition stored in the registry.
+pub trait Definition {
+    const KIND: DefKind;
+
+    fn id(&self) -> &str;
+}
+
+impl Definition for ItemDef {
+    const KIND: DefKind = DefKind::Item;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for BenchDef {
+    const KIND: DefKind = DefKind::Bench;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for RecipeDef {
+    const KIND: DefKind = DefKind::Recipe;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for LootTableDef {
+    const KIND: DefKind = DefKind::LootTable;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for StatusDef {
+    const KIND: DefKind = DefKind::Status;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for EnemyDef {
+    const KIND: DefKind = DefKind::Enemy;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for NoiseKindDef {
+    const KIND: DefKind = DefKind::NoiseKind;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for FactionDef {
+    const KIND: DefKind = DefKind::Faction;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for DialogueDef {
+    const KIND: DefKind = DefKind::Dialogue;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for QuestDef {
+    const KIND: DefKind = DefKind::Quest;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Definition for TagDef {
+    const KIND: DefKind = DefKind::Tag;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Where a definition was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefSource {
+    pub mod_id: String,
+    /// Path of the data file, relative to the mod root.
+    pub file: PathBuf,
+}
+
+impl fmt::Display for DefSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.mod_id, self.file.display())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOp {
+    /// First definition of the id.
+    Define,
+    /// A later mod supplied a full definition for the same id.
+    Replace,
+    Patch,
+    Remove,
+}
+
+/// One change applied to a definition id, in load order.
+#[derive(Debug, Clone)]
+pub struct Touch {
+    pub op: MergeOp,
+    pub source: DefSource,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegistryEntry<T> {
+    pub def: T,
+    /// Every change applied to this id, oldest first. Never empty.
+    pub history: Vec<Touch>,
+}
+
+impl<T> RegistryEntry<T> {
+    /// The file holding the full definition that patches were applied on top of.
+    pub fn defined_by(&self) -> &DefSource {
+        self.history
+            .iter()
+            .rev()
+            .find(|touch| matches!(touch.op, MergeOp::Define | MergeOp::Replace))
+            .map(|touch| &touch.source)
+            .expect("registry entries always start with a definition")
+    }
+
+    pub fn last_touched_by(&self) -> &DefSource {
+        &self
+            .history
+            .last()
+            .expect("registry entries always start with a definition")
+            .source
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MergeError {
+    #[error("duplicate {kind} id '{id}' in {at} (already defined in {existing})")]
+    DuplicateId {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+        existing: DefSource,
+    },
+
+    #[error(
+        "{at} replaces {kind} '{id}' last defined in {existing}, but '{}' does not depend on '{}'",
+        at.mod_id,
+        existing.mod_id
+    )]
+    ConflictingReplace {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+        existing: DefSource,
+    },
+
+    #[error("{at} defines {kind} '{id}' in the namespace of another mod")]
+    ForeignNamespace {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+    },
+
+    #[error(
+        "{at} patches {kind} '{id}' defined in {existing}, but '{}' does not depend on '{}'",
+        at.mod_id,
+        existing.mod_id
+    )]
+    ConflictingPatch {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+        existing: DefSource,
+    },
+
+    #[error(
+        "{at} removes {kind} '{id}' defined in {existing}, but '{}' does not depend on '{}'",
+        at.mod_id,
+        existing.mod_id
+    )]
+    ConflictingRemove {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+        existing: DefSource,
+    },
+
+    #[error("{at} patches {kind} '{id}', which is not defined")]
+    PatchTargetMissing {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+    },
+
+    #[error("{at} removes {kind} '{id}', which is not defined")]
+    RemoveTargetMissing {
+        kind: DefKind,
+        id: String,
+        at: DefSource,
+    },
+}
+
+/// Definitions of a single kind, keyed (and iterated) by id.
+#[derive(Debug, Clone)]
+pub struct DefMap<T> {
+    entries: BTreeMap<String, RegistryEntry<T>>,
+    removed: BTreeMap<String, Touch>,
+}
+
+impl<T> Default for DefMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            removed: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Definition> DefMap<T> {
+    pub fn get(&self, id: &str) -> Option<&T> {
+        self.entries.get(id).map(|entry| &entry.def)
+    }
+
+    pub fn entry(&self, id: &str) -> Option<&RegistryEntry<T>> {
+        self.entries.get(id)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.values().map(|entry| &entry.def)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &RegistryEntry<T>> {
+        self.entries.values()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Ids removed by a `Remove(...)` entry and not defined again since.
+    pub fn removed(&self) -> impl Iterator<Item = (&str, &Touch)> {
+        self.removed.iter().map(|(id, touch)| (id.as_str(), touch))
+    }
+
+    /// The full id `id` refers to: `id` itself if defined, or else the only
+    /// definition whose local id is `id`. Meant for ids typed by a person.
+    pub fn qualified_id<'s>(&'s self, id: &'s str) -> Option<&'s str> {
+        if self.entries.contains_key(id) {
+            return Some(id);
+        }
+        let mut matches = self.ids().filter(|full| local_id(full) == id);
+        let found = matches.next()?;
+        matches.next().is_none().then_some(found)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a full definition, replacing an earlier one only if the defining
+    /// mod is in `dependencies` (the transitive dependencies of `source.mod_id`).
+    pub(crate) fn define(
+        &mut self,
+        def: T,
+        source: DefSource,
+        dependencies: &BTreeSet<String>,
+    ) -> Result<(), MergeError> {
+        let Some(entry) = self.entries.get_mut(def.id()) else {
+            // Other mods' namespaces are only written to by replacing.
+            if split_id(def.id()).is_some_and(|(namespace, _)| namespace != source.mod_id) {
+                return Err(MergeError::ForeignNamespace {
+                    kind: T::KIND,
+                    id: def.id().to_owned(),
+                    at: source,
+                });
+            }
+
+            // Only a definition that went in undoes an earlier removal.
+            self.removed.remove(def.id());
+            self.entries.insert(
+                def.id().to_owned(),
+                RegistryEntry {
+                    def,
+                    history: vec![Touch {
+                        op: MergeOp::Define,
+                        source,
+                    }],
+                },
+            );
+            return Ok(());
+        };
+
+        let existing = entry.defined_by().clone();
+        if existing.mod_id == source.mod_id {
+            return Err(MergeError::DuplicateId {
+                kind: T::KIND,
+                id: def.id().to_owned(),
+                at: source,
+                existing,
+            });
+        }
+        if !dependencies.contains(&existing.mod_id) {
+            return Err(MergeError::ConflictingReplace {
+                kind: T::KIND,
+                id: def.id().to_owned(),
+                at: source,
+                existing,
+            });
+        }
+
+        self.removed.remove(def.id());
+        entry.def = def;
+        entry.history.push(Touch {
+            op: MergeOp::Replace,
+            source,
+        });
+        Ok(())
+    }
+
+    /// Changes fields of a definition, which like replacing needs the mod
+    /// that defined it to be the patching mod or in its `dependencies`.
+    pub(crate) fn patch<P>(
+        &mut self,
+        patch: &P,
+        source: DefSource,
+        dependencies: &BTreeSet<String>,
+    ) -> Result<(), MergeError>
+    where
+        P: Patch<Target = T>,
+    {
+        let Some(entry) = self.entries.get_mut(patch.id()) else {
+            return Err(MergeError::PatchTargetMissing {
+                kind: T::KIND,
+                id: patch.id().to_owned(),
+                at: source,
+            });
+        };
+        let existing = entry.defined_by();
+        if existing.mod_id != source.mod_id && !dependencies.contains(&existing.mod_id) {
+            return Err(MergeError::ConflictingPatch {
+                kind: T::KIND,
+                id: patch.id().to_owned(),
+                existing: existing.clone(),
+                at: source,
+            });
+        }
+
+        patch.apply(&mut entry.def);
+        entry.history.push(Touch {
+            op: MergeOp::Patch,
+            source,
+        });
+        Ok(())
+    }
+
+    /// Deletes a definition, under the same dependency rule as [`Self::patch`].
+    pub(crate) fn remove(
+        &mut self,
+        id: &str,
+        source: DefSource,
+        dependencies: &BTreeSet<String>,
+    ) -> Result<(), MergeError> {
+        let Some(entry) = self.entries.get(id) else {
+            return Err(MergeError::RemoveTargetMissing {
+                kind: T::KIND,
+                id: id.to_owned(),
+                at: source,
+            });
+        };
+        let existing = entry.defined_by();
+        if existing.mod_id != source.mod_id && !dependencies.contains(&existing.mod_id) {
+            return Err(MergeError::ConflictingRemove {
+                kind: T::KIND,
+                id: id.to_owned(),
+                existing: existing.clone(),
+                at: source,
+            });
+        }
+        self.entries.remove(id);
+
+        self.removed.insert(
+            id.to_owned(),
+            Touch {
+                op: MergeOp::Remove,
+                source,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// A mod that contributed to the registry, in load order.
+#[derive(Debug, Clone)]
+pub struct LoadedMod {
+    pub manifest: ModManifest,
+    pub fs: ModFs,
+}
+
+/// Merged content of every loaded mod, keyed by id.
+#[derive(Debug, Clone, Default)]
+pub struct ContentRegistry {
+    pub mods: Vec<LoadedMod>,
+    pub items: DefMap<ItemDef>,
+    pub benches: DefMap<BenchDef>,
+    pub recipes: DefMap<RecipeDef>,
+    pub loot_tables: DefMap<LootTableDef>,
+    pub tags: DefMap<TagDef>,
+    pub statuses: DefMap<StatusDef>,
+    pub enemies: DefMap<EnemyDef>,
+    pub noise_kinds: DefMap<NoiseKindDef>,
+    pub factions: DefMap<FactionDef>,
+    pub dialogues: DefMap<DialogueDef>,
+    pub quests: DefMap<QuestDef>,
+    /// Component rules added by mods, on top of [`BUILTIN_COMPONENT_RULES`].
+    pub component_rules: Vec<(ComponentRule, DefSource)>,
+    /// Localized text from every mod's `lang/` folder.
+    pub translations: Translations,
+}
+
+impl ContentRegistry {
+    pub fn loaded_mod(&self, mod_id: &str) -> Option<&LoadedMod> {
+        self.mods.iter().find(|m| m.manifest.mod_id == mod_id)
+    }
+
+    pub fn contains(&self, kind: DefKind, id: &str) -> bool {
+        match kind {
+            DefKind::Item => self.items.contains(id),
+            DefKind::Bench => self.benches.contains(id),
+            DefKind::Recipe => self.recipes.contains(id),
+            DefKind::LootTable => self.loot_tables.contains(id),
+            DefKind::Tag => self.tags.contains(id),
+            DefKind::Status => self.statuses.contains(id),
+            DefKind::Enemy => self.enemies.contains(id),
+            DefKind::NoiseKind => self.noise_kinds.contains(id),
+            DefKind::Faction => self.factions.contains(id),
+            DefKind::Dialogue => self.dialogues.contains(id),
+            DefKind::Quest => self.quests.contains(id),
+        }
+    }
+
+    /// Built-in component rules followed by those added by mods.
+    pub fn component_rules(&self) -> impl Iterator<Item = &ComponentRule> {
+        BUILTIN_COMPONENT_RULES
+            .iter()
+            .chain(self.component_rules.iter().map(|(rule, _)| rule))
+    }
+
+    /// Every mod `mod_id` depends on, directly or transitively. All mods
+    /// implicitly depend on `base`.
+    pub fn dependencies_of(&self, mod_id: &str) -> BTreeSet<String> {
+        let mut found = BTreeSet::new();
+        if mod_id != BASE_MOD_ID {
+            found.insert(BASE_MOD_ID.to_owned());
+        }
+
+        let mut stack = vec![mod_id];
+        while let Some(current) = stack.pop() {
+            let Some(loaded) = self.loaded_mod(current) else {
+                continue;
+            };
+            for dep in &loaded.manifest.dependencies {
+                if found.insert(dep.mod_id.clone()) {
+                    stack.push(&dep.mod_id);
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Merges one data file entry into the registry.
+    ///
+    /// The mod named by `source` must already be in [`ContentRegistry::mods`].
+    pub fn apply(&mut self, doc: ContentDoc, source: DefSource) -> Result<(), MergeError> {
+        let dependencies = self.dependencies_of(&source.mod_id);
+
+        match doc {
+            ContentDoc::Item(def) => self.items.define(def, source, &dependencies),
+            ContentDoc::Bench(def) => self.benches.define(def, source, &dependencies),
+            ContentDoc::Recipe(def) => self.recipes.define(def, source, &dependencies),
+            ContentDoc::LootTable(def) => self.loot_tables.define(def, source, &dependencies),
+            ContentDoc::Tag(def) => self.tags.define(def, source, &dependencies),
+            ContentDoc::Status(def) => self.statuses.define(def, source, &dependencies),
+            ContentDoc::Enemy(def) => self.enemies.define(def, source, &dependencies),
+            ContentDoc::NoiseKind(def) => self.noise_kinds.define(def, source, &dependencies),
+            ContentDoc::Faction(def) => self.factions.define(def, source, &dependencies),
+            ContentDoc::Dialogue(def) => self.dialogues.define(def, source, &dependencies),
+            ContentDoc::Quest(def) => self.quests.define(def, source, &dependencies),
+            ContentDoc::Patch(PatchDef::Item(patch)) => {
+                self.items.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Bench(patch)) => {
+                self.benches.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Recipe(patch)) => {
+                self.recipes.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::LootTable(patch)) => {
+                self.loot_tables.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Status(patch)) => {
+                self.statuses.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Enemy(patch)) => {
+                self.enemies.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::NoiseKind(patch)) => {
+                self.noise_kinds.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Faction(patch)) => {
+                self.factions.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Tag(patch)) => {
+                self.tags.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Dialogue(patch)) => {
+                self.dialogues.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Patch(PatchDef::Quest(patch)) => {
+                self.quests.patch(&patch, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Item(id)) => self.items.remove(&id, source, &dependencies),
+            ContentDoc::Remove(DefRef::Bench(id)) => {
+                self.benches.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Recipe(id)) => {
+                self.recipes.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::LootTable(id)) => {
+                self.loot_tables.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Tag(id)) => self.tags.remove(&id, source, &dependencies),
+            ContentDoc::Remove(DefRef::Status(id)) => {
+                self.statuses.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Enemy(id)) => {
+                self.enemies.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::NoiseKind(id)) => {
+                self.noise_kinds.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Faction(id)) => {
+                self.factions.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Dialogue(id)) => {
+                self.dialogues.remove(&id, source, &dependencies)
+            }
+            ContentDoc::Remove(DefRef::Quest(id)) => self.quests.remove(&id, source, &dependencies),
+            ContentDoc::ComponentRule(rule) => {
+                self.component_rules.push((rule, source));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::patches::TagPatch;
+
+    fn tag(id: &str) -> TagDef {
+        TagDef {
+            id: id.to_owned(),
+            parent: None,
+        }
+    }
+
+    fn source(mod_id: &str) -> DefSource {
+        DefSource {
+            mod_id: mod_id.to_owned(),
+            file: PathBuf::from("data/tags.ron"),
+        }
+    }
+
+    #[test]
+    fn failed_define_keeps_the_removal() {
+        let mut tags = DefMap::default();
+        let deps = BTreeSet::from([BASE_MOD_ID.to_owned()]);
+        tags.define(tag("base:shiny"), source("base"), &BTreeSet::new())
+            .unwrap();
+        tags.remove("base:shiny", source("extra"), &deps).unwrap();
+
+        // A third mod cannot define an id in the base namespace from scratch.
+        let err = tags
+            .define(tag("base:shiny"), source("other"), &deps)
+            .unwrap_err();
+        assert!(matches!(err, MergeError::ForeignNamespace { .. }));
+
+        let removed: Vec<_> = tags
+            .removed()
+            .map(|(id, touch)| (id, &touch.source.mod_id))
+            .collect();
+        assert_eq!(removed, [("base:shiny", &"extra".to_owned())]);
+        assert!(!tags.contains("base:shiny"));
+    }
+
+    #[test]
+    fn define_after_remove_clears_the_removal() {
+        let mut tags = DefMap::default();
+        tags.define(tag("base:shiny"), source("base"), &BTreeSet::new())
+            .unwrap();
+        tags.remove("base:shiny", source("base"), &BTreeSet::new())
+            .unwrap();
+        tags.define(tag("base:shiny"), source("base"), &BTreeSet::new())
+            .unwrap();
+
+        assert_eq!(tags.removed().count(), 0);
+        assert!(tags.contains("base:shiny"));
+    }
+
+    #[test]
+    fn patch_and_remove_need_a_dependency_on_the_definer() {
+        let mut tags = DefMap::default();
+        let on_base = BTreeSet::from([BASE_MOD_ID.to_owned()]);
+        tags.define(tag("base:shiny"), source("base"), &BTreeSet::new())
+            .unwrap();
+        tags.define(tag("other:dull"), source("other"), &on_base)
+            .unwrap();
+
+        let patch = TagPatch {
+            id: "other:dull".to_owned(),
+            parent: Some("base:shiny".to_owned()),
+        };
+        let err = tags.patch(&patch, source("extra"), &on_base).unwrap_err();
+        assert!(matches!(err, MergeError::ConflictingPatch { .. }));
+        let err = tags
+            .remove("other:dull", source("extra"), &on_base)
+            .unwrap_err();
+        assert!(matches!(err, MergeError::ConflictingRemove { .. }));
+        assert_eq!(tags.get("other:dull").unwrap().parent, None);
+
+        let on_other = BTreeSet::from([BASE_MOD_ID.to_owned(), "other".to_owned()]);
+        tags.patch(&patch, source("extra"), &on_other).unwrap();
+        assert_eq!(
+            tags.get("other:dull").unwrap().parent.as_deref(),
+            Some("base:shiny")
+        );
+        tags.remove("other:dull", source("extra"), &on_other)
+            .unwrap();
+        assert!(!tags.contains("other:dull"));
+
+        // A mod may always change its own definitions.
+        tags.remove("base:shiny", source("base"), &BTreeSet::new())
+            .unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use semver::{Version, VersionReq};
+use thiserror::Error;
+
+use crate::loader::{BASE_MOD_ID, DiscoveredMod};
+
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    #[error("mod '{mod_id}' has an invalid version '{version}': {source}")]
+    InvalidVersion {
+        mod_id: String,
+        version: String,
+        source: semver::Error,
+    },
+
+    #[error(
+        "mod '{mod_id}' has an invalid version requirement '{requirement}' on '{dependency}': {source}"
+    )]
+    InvalidRequirement {
+        mod_id: String,
+        dependency: String,
+        requirement: String,
+        source: semver::Error,
+    },
+
+    #[error("mod '{mod_id}' is enabled but was not found")]
+    UnknownMod { mod_id: String },
+
+    #[error("mod '{mod_id}' depends on '{dependency}', which was not found")]
+    MissingDependency { mod_id: String, dependency: String },
+
+    #[error("mod '{mod_id}' requires '{dependency}' {required}, but version {found} is installed")]
+    VersionMismatch {
+        mod_id: String,
+        dependency: String,
+        required: VersionReq,
+        found: Version,
+    },
+
+    #[error("the base mod must not declare dependencies (found '{dependency}')")]
+    BaseHasDependencies { dependency: String },
+
+    #[error("dependency cycle: {}", cycle.join(" -> "))]
+    Cycle { cycle: Vec<String> },
+}
+
+/// Orders `mods` so that every mod comes after its dependencies.
+///
+/// When `enabled` is given, only those mods (plus `base` and anything they
+/// depend on) are kept, and its order breaks ties between independent mods.
+/// Otherwise ties are broken by mod id. `base` always comes first.
+pub fn resolve_load_order(
+    mods: Vec<DiscoveredMod>,
+    enabled: Option<&[String]>,
+) -> Result<Vec<DiscoveredMod>, ResolveError> {
+    let mut available: BTreeMap<String, DiscoveredMod> = mods
+        .into_iter()
+        .map(|m| (m.manifest.mod_id.clone(), m))
+        .collect();
+
+    let roots: Vec<String> = match enabled {
+        Some(enabled) => {
+            let mut roots = Vec::new();
+            if available.contains_key(BASE_MOD_ID) {
+                roots.push(BASE_MOD_ID.to_owned());
+            }
+            for mod_id in enabled {
+                if !available.contains_key(mod_id) {
+                    return Err(ResolveError::UnknownMod {
+                        mod_id: mod_id.clone(),
+                    });
+                }
+                roots.push(mod_id.clone());
+            }
+            roots
+        }
+        None => available.keys().cloned().collect(),
+    };
+
+    // Pull in everything the selected mods depend on, checking versions on the way.
+    let mut selected = BTreeSet::new();
+    let mut stack = roots.clone();
+    while let Some(mod_id) = stack.pop() {
+        if !selected.insert(mod_id.clone()) {
+            continue;
+        }
+
+        let manifest = &available[&mod_id].manifest;
+        if mod_id == BASE_MOD_ID
+            && let Some(dep) = manifest.dependencies.first()
+        {
+            return Err(ResolveError::BaseHasDependencies {
+                dependency: dep.mod_id.clone(),
+            });
+        }
+
+        for dep in &manifest.dependencies {
+            let Some(target) = available.get(&dep.mod_id) else {
+                return Err(ResolveError::MissingDependency {
+                    mod_id: mod_id.clone(),
+                    dependency: dep.mod_id.clone(),
+                });
+            };
+
+            let required = VersionReq::parse(&dep.version).map_err(|source| {
+                ResolveError::InvalidRequirement {
+                    mod_id: mod_id.clone(),
+                    dependency: dep.mod_id.clone(),
+                    requirement: dep.version.clone(),
+                    source,
+                }
+            })?;
+            let found = parse_version(&target.manifest.mod_id, &target.manifest.version)?;
+            if !required.matches(&found) {
+                return Err(ResolveError::VersionMismatch {
+                    mod_id: mod_id.clone(),
+                    dependency: dep.mod_id.clone(),
+                    required,
+                    found,
+                });
+            }
+
+            stack.push(dep.mod_id.clone());
+        }
+    }
+
+    for mod_id in &selected {
+        parse_version(mod_id, &available[mod_id].manifest.version)?;
+    }
+
+    // Kahn's algorithm; the ready set is ordered by (not base, configured rank, id).
+    let rank = |mod_id: &str| -> (bool, usize, String) {
+        let position = enabled
+            .and_then(|enabled| enabled.iter().position(|m| m == mod_id))
+            .unwrap_or(usize::MAX);
+        (mod_id != BASE_MOD_ID, position, mod_id.to_owned())
+    };
+
+    let mut pending: BTreeMap<&str, BTreeSet<&str>> = selected
+        .iter()
+        .map(|mod_id| {
+            let deps = available[mod_id]
+                .manifest
+                .dependencies
+                .iter()
+                .map(|dep| dep.mod_id.as_str())
+                .collect();
+            (mod_id.as_str(), deps)
+        })
+        .collect();
+
+    let mut ready: BTreeSet<(bool, usize, String)> = pending
+        .iter()
+        .filter(|(_, deps)| deps.is_empty())
+        .map(|(mod_id, _)| rank(mod_id))
+        .collect();
+
+    let mut order = Vec::with_capacity(selected.len());
+    while let Some(next) = ready.pop_first() {
+        let mod_id = next.2;
+        pending.remove(mod_id.as_str());
+        for (other, deps) in pending.iter_mut() {
+            if deps.remove(mod_id.as_str()) && deps.is_empty() {
+                ready.insert(rank(other));
+            }
+        }
+        order.push(mod_id);
+    }
+
+    if !pending.is_empty() {
+        return Err(ResolveError::Cycle {
+            cycle: find_cycle(&pending),
+        });
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|mod_id| available.remove(&mod_id))
+        .collect())
+}
+
+fn parse_version(mod_id: &str, version: &str) -> Result<Version, ResolveError> {
+    Version::parse(version).map_err(|source| ResolveError::InvalidVersion {
+        mod_id: mod_id.to_owned(),
+        version: version.to_owned(),
+        source,
+    })
+}
+
+/// Walks unresolved dependency edges until a mod repeats. Every mod left in
+/// `pending` has at least one unresolved dependency, so this always terminates.
+fn find_cycle(pending: &BTreeMap<&str, BTreeSet<&str>>) -> Vec<String> {
+    let mut path: Vec<&str> = Vec::new();
+    let mut current = *pending.keys().next().expect("pending is not empty");
+
+    loop {
+        if let Some(start) = path.iter().position(|m| *m == current) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|m| m.to_string()).collect();
+            cycle.push(current.to_owned());
+            return cycle;
+        }
+
+        path.push(current);
+        current = pending[current]
+            .iter()
+            .find(|dep| pending.contains_key(*dep))
+            .copied()
+            .expect("unresolved mods only depend on other unresolved mods");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        manifest::{ModDependency, ModManifest},
+        vfs::ModFs,
+    };
+
+    use super::*;
+
+    fn discovered(mod_id: &str, version: &str, deps: &[(&str, &str)]) -> DiscoveredMod {
+        DiscoveredMod {
+            manifest: ModManifest {
+                mod_id: mod_id.to_owned(),
+                name: mod_id.to_owned(),
+                version: version.to_owned(),
+                mod_api_version: 2,
+                dependencies: deps
+                    .iter()
+                    .map(|(mod_id, version)| ModDependency {
+                        mod_id: (*mod_id).to_owned(),
+                        version: (*version).to_owned(),
+                    })
+                    .collect(),
+                description: None,
+                authors: None,
+            },
+            fs: ModFs::open_dir(&std::env::temp_dir()).unwrap(),
+        }
+    }
+
+    fn order(mods: Vec<DiscoveredMod>, enabled: Option<&[String]>) -> Vec<String> {
+        resolve_load_order(mods, enabled)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.manifest.mod_id)
+            .collect()
+    }
+
+    #[test]
+    fn dependencies_load_first_and_base_leads() {
+        let mods = vec![
+            discovered("zeta", "1.0.0", &[("alpha", ">=1.0.0")]),
+            discovered("alpha", "1.2.0", &[]),
+            discovered("base", "0.1.0", &[]),
+        ];
+        assert_eq!(order(mods, None), ["base", "alpha", "zeta"]);
+    }
+
+    #[test]
+    fn enabled_order_breaks_ties_and_pulls_in_dependencies() {
+        let mods = vec![
+            discovered("base", "0.1.0", &[]),
+            discovered("a", "1.0.0", &[]),
+            discovered("b", "1.0.0", &[("c", "^0.2")]),
+            discovered("c", "0.2.5", &[]),
+            discovered("unused", "1.0.0", &[]),
+        ];
+        let enabled = ["b".to_owned(), "a".to_owned()];
+        // `c` is not enabled itself, so it ranks after every enabled mod that is ready.
+        assert_eq!(order(mods, Some(&enabled)), ["base", "a", "c", "b"]);
+    }
+
+    #[test]
+    fn semver_ranges_are_checked() {
+        let caret = vec![
+            discovered("a", "1.0.0", &[("b", "^0.2")]),
+            discovered("b", "0.3.0", &[]),
+        ];
+        let err = resolve_load_order(caret, None).unwrap_err();
+        assert!(matches!(
+            err,
+            ResolveError::VersionMismatch { ref dependency, .. } if dependency == "b"
+        ));
+
+        let at_least = vec![
+            discovered("a", "1.0.0", &[("b", ">=1.0.0")]),
+            discovered("b", "0.9.9", &[]),
+        ];
+        assert!(matches!(
+            resolve_load_order(at_least, None),
+            Err(ResolveError::VersionMismatch { .. })
+        ));
+
+        let bad_requirement = vec![
+            discovered("a", "1.0.0", &[("b", "not a range")]),
+            discovered("b", "1.0.0", &[]),
+        ];
+        assert!(matches!(
+            resolve_load_order(bad_requirement, None),
+            Err(ResolveError::InvalidRequirement { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_dependencies_are_reported() {
+        let mods = vec![discovered("a", "1.0.0", &[("ghost", "^1")])];
+        let err = resolve_load_order(mods, None).unwrap_err();
+        assert!(matches!(
+            err,
+            ResolveError::MissingDependency { ref mod_id, ref dependency }
+                if mod_id == "a" && dependency == "ghost"
+        ));
+
+        let enabled = ["nope".to_owned()];
+        assert!(matches!(
+            resolve_load_order(vec![discovered("a", "1.0.0", &[])], Some(&enabled)),
+            Err(ResolveError::UnknownMod { .. })
+        ));
+    }
+
+    #[test]
+    fn cycles_are_reported_with_their_path() {
+        let mods = vec![
+            discovered("a", "1.0.0", &[("b", "*")]),
+            discovered("b", "1.0.0", &[("c", "*")]),
+            discovered("c", "1.0.0", &[("a", "*")]),
+        ];
+        let err = resolve_load_order(mods, None).unwrap_err();
+        let ResolveError::Cycle { cycle } = err else {
+            panic!("expected a cycle, got {err}");
+        };
+        assert_eq!(cycle, ["a", "b", "c", "a"]);
+    }
+}
@@ -1,4 +1,13 @@
 pub mod benches;
+pub mod dialogue;
+pub mod document;
+pub mod enemies;
+pub mod factions;
 pub mod items;
 pub mod loot;
+pub mod noise;
+pub mod patches;
+pub mod quests;
 pub mod recipes;
+pub mod status;
+pub mod tags;
@@ -1,12 +1,15 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Bench")]
 pub struct BenchDef {
     pub id: String,
     pub name: String,
     pub craft_speed_mult: f32,
     pub noise_mult: f32,
 
-    #[serde(default)]
+    /// Tag patterns (`weapon`, `weapon.*`, `*`) of the recipes this bench
+    /// accepts; empty accepts every recipe.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub allowed_recipe_tags: Vec<String>,
 }
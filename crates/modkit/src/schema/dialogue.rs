@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use super::recipes::ItemStackDef;
+
+/// Who says a line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Speaker {
+    Player,
+    /// An NPC, by the text key of its name.
+    Npc(String),
+}
+
+/// What must hold for a choice to be offered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DialogueCondition {
+    HasItem(ItemStackDef),
+    /// The player's reputation with the faction is at least `at_least`.
+    Reputation {
+        faction: String,
+        at_least: i32,
+    },
+    QuestActive(String),
+    QuestComplete(String),
+}
+
+/// What happens when a choice is picked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DialogueEffect {
+    GiveItem(ItemStackDef),
+    TakeItem(ItemStackDef),
+    /// Adds `change`, which may be negative, to the player's reputation.
+    Reputation {
+        faction: String,
+        change: i32,
+    },
+    StartQuest(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueChoice {
+    /// Text key of the choice.
+    pub text: String,
+
+    /// Node the choice leads to; without one, picking it ends the dialogue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+
+    /// All must hold for the choice to be offered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<DialogueCondition>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<DialogueEffect>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueNode {
+    /// Unique within the dialogue.
+    pub id: String,
+    pub speaker: Speaker,
+    /// Text key of the line.
+    pub text: String,
+
+    /// A node without choices ends the dialogue.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<DialogueChoice>,
+}
+
+/// A conversation: a graph of nodes linked by the choices in them.
+///
+/// Text lives in `lang/<locale>.ron`; nodes and choices only hold keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Dialogue")]
+pub struct DialogueDef {
+    pub id: String,
+    /// Id of the node the dialogue opens with.
+    pub start: String,
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl DialogueDef {
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use crate::registry::DefKind;
+
+use super::{
+    benches::BenchDef,
+    dialogue::DialogueDef,
+    enemies::EnemyDef,
+    factions::FactionDef,
+    items::{ComponentRule, ItemDef},
+    loot::LootTableDef,
+    noise::NoiseKindDef,
+    patches::PatchDef,
+    quests::QuestDef,
+    recipes::RecipeDef,
+    status::StatusDef,
+    tags::TagDef,
+};
+
+/// The top-level value of a data file.
+///
+/// A plain definition adds new content or fully replaces an existing id,
+/// `Patch(Item(...))` changes individual fields and `Remove(Item("id"))`
+/// deletes a definition loaded by an earlier mod. `ComponentRule(...)` adds
+/// a constraint every item is checked against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ContentDoc {
+    Item(ItemDef),
+    Bench(BenchDef),
+    Recipe(RecipeDef),
+    LootTable(LootTableDef),
+    Tag(TagDef),
+    Status(StatusDef),
+    Enemy(EnemyDef),
+    NoiseKind(NoiseKindDef),
+    Faction(FactionDef),
+    Dialogue(DialogueDef),
+    Quest(QuestDef),
+    Patch(PatchDef),
+    Remove(DefRef),
+    ComponentRule(ComponentRule),
+}
+
+/// A whole data file: one entry, or a list of entries of any kinds.
+///
+/// Serializes as the bare entry or list, the same shape it was read from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum DataFile {
+    Single(Box<ContentDoc>),
+    List(Vec<ContentDoc>),
+}
+
+impl DataFile {
+    /// The entries of the file, in the order they are written.
+    pub fn into_docs(self) -> Vec<ContentDoc> {
+        match self {
+            DataFile::Single(doc) => vec![*doc],
+            DataFile::List(docs) => docs,
+        }
+    }
+}
+
+/// Names a definition by kind and id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DefRef {
+    Item(String),
+    Bench(String),
+    Recipe(String),
+    LootTable(String),
+    Tag(String),
+    Status(String),
+    Enemy(String),
+    NoiseKind(String),
+    Faction(String),
+    Dialogue(String),
+    Quest(String),
+}
+
+impl ContentDoc {
+    /// The kind of definition this entry adds, patches or removes, or `None`
+    /// for entries that are not about a single definition.
+    pub fn kind(&self) -> Option<DefKind> {
+        let kind = match self {
+            ContentDoc::Item(_) => DefKind::Item,
+            ContentDoc::Bench(_) => DefKind::Bench,
+            ContentDoc::Recipe(_) => DefKind::Recipe,
+            ContentDoc::LootTable(_) => DefKind::LootTable,
+            ContentDoc::Tag(_) => DefKind::Tag,
+            ContentDoc::Status(_) => DefKind::Status,
+            ContentDoc::Enemy(_) => DefKind::Enemy,
+            ContentDoc::NoiseKind(_) => DefKind::NoiseKind,
+            ContentDoc::Faction(_) => DefKind::Faction,
+            ContentDoc::Dialogue(_) => DefKind::Dialogue,
+            ContentDoc::Quest(_) => DefKind::Quest,
+            ContentDoc::Patch(patch) => match patch {
+                PatchDef::Item(_) => DefKind::Item,
+                PatchDef::Bench(_) => DefKind::Bench,
+                PatchDef::Recipe(_) => DefKind::Recipe,
+                PatchDef::LootTable(_) => DefKind::LootTable,
+                PatchDef::Status(_) => DefKind::Status,
+                PatchDef::Enemy(_) => DefKind::Enemy,
+                PatchDef::NoiseKind(_) => DefKind::NoiseKind,
+                PatchDef::Faction(_) => DefKind::Faction,
+                PatchDef::Tag(_) => DefKind::Tag,
+                PatchDef::Dialogue(_) => DefKind::Dialogue,
+                PatchDef::Quest(_) => DefKind::Quest,
+            },
+            ContentDoc::Remove(target) => match target {
+                DefRef::Item(_) => DefKind::Item,
+                DefRef::Bench(_) => DefKind::Bench,
+                DefRef::Recipe(_) => DefKind::Recipe,
+                DefRef::LootTable(_) => DefKind::LootTable,
+                DefRef::Tag(_) => DefKind::Tag,
+                DefRef::Status(_) => DefKind::Status,
+                DefRef::Enemy(_) => DefKind::Enemy,
+                DefRef::NoiseKind(_) => DefKind::NoiseKind,
+                DefRef::Faction(_) => DefKind::Faction,
+                DefRef::Dialogue(_) => DefKind::Dialogue,
+                DefRef::Quest(_) => DefKind::Quest,
+            },
+            ContentDoc::ComponentRule(_) => return None,
+        };
+        Some(kind)
+    }
+}
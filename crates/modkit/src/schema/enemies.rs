@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::items::WeaponDef;
+
+/// Where the AI script of a behavior profile lives in its mod:
+/// `dog_patrol` is `scripts/ai/dog_patrol.rhai`.
+pub fn behavior_script_path(local_id: &str) -> String {
+    format!("scripts/ai/{local_id}.rhai")
+}
+
+/// An enemy type, such as a patrol dog or a K9 unit.
+///
+/// Distances are in world units and speeds in world units per second.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Enemy")]
+pub struct EnemyDef {
+    pub id: String,
+    pub name: String,
+
+    pub health: f32,
+    pub move_speed: f32,
+    pub chase_speed: f32,
+
+    /// The quietest noise, as loudness where the enemy stands, it reacts to.
+    pub hearing_threshold: f32,
+    pub vision_range: f32,
+    /// Full width of the vision cone, in degrees.
+    pub vision_cone_deg: f32,
+
+    pub attack: WeaponDef,
+
+    /// Loot table rolled when the enemy dies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drops: Option<String>,
+
+    /// Behavior profile id; see [`behavior_script_path`].
+    pub behavior: String,
+
+    /// Path under the mod's `assets/` folder.
+    pub sprite: PathBuf,
+}
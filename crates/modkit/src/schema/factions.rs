@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{items::StatModifier, recipes::ItemStackDef};
+
+/// How a faction treats someone.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Stance {
+    Hostile,
+    #[default]
+    Neutral,
+    Friendly,
+}
+
+/// Reputation at which a player's standing with a faction changes. Anything
+/// between the two is neutral.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ReputationThresholds {
+    /// At or below this, the faction is hostile.
+    pub hostile: i32,
+    /// At or above this, the faction is friendly.
+    pub friendly: i32,
+}
+
+/// What a player gets on reaching a stance with a faction. Negative effects,
+/// such as a `Cooldown` factor above 1, work as penalties.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StanceEffects {
+    /// Given once, each time the player reaches the stance.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemStackDef>,
+
+    /// Applied while the player holds the stance.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<StatModifier>,
+}
+
+/// A group of NPCs, such as a colony of allied cats or a rival gang.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Faction")]
+pub struct FactionDef {
+    pub id: String,
+    pub name: String,
+
+    /// Stance toward players who have no reputation with the faction yet.
+    #[serde(default)]
+    pub default_stance: Stance,
+
+    /// Stance toward other factions, by faction id. Unlisted factions are
+    /// neutral.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stances: BTreeMap<String, Stance>,
+
+    pub thresholds: ReputationThresholds,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rewards: BTreeMap<Stance, StanceEffects>,
+}
+
+impl FactionDef {
+    /// The faction's stance toward a player with `reputation`.
+    pub fn stance_for(&self, reputation: i32) -> Stance {
+        if reputation <= self.thresholds.hostile {
+            Stance::Hostile
+        } else if reputation >= self.thresholds.friendly {
+            Stance::Friendly
+        } else {
+            Stance::Neutral
+        }
+    }
+
+    /// The faction's stance toward `other`. A faction is friendly to itself.
+    pub fn stance_toward(&self, other: &str) -> Stance {
+        if other == self.id {
+            return Stance::Friendly;
+        }
+        self.stances.get(other).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faction(stances: &[(&str, Stance)]) -> FactionDef {
+        FactionDef {
+            id: "alley_cats".to_owned(),
+            name: "Alley Cats".to_owned(),
+            default_stance: Stance::Neutral,
+            stances: stances
+                .iter()
+                .map(|&(id, stance)| (id.to_owned(), stance))
+                .collect(),
+            thresholds: ReputationThresholds {
+                hostile: -10,
+                friendly: 10,
+            },
+            rewards: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn thresholds_are_inclusive() {
+        let faction = faction(&[]);
+        assert_eq!(faction.stance_for(-11), Stance::Hostile);
+        assert_eq!(faction.stance_for(-10), Stance::Hostile);
+        assert_eq!(faction.stance_for(-9), Stance::Neutral);
+        assert_eq!(faction.stance_for(0), Stance::Neutral);
+        assert_eq!(faction.stance_for(9), Stance::Neutral);
+        assert_eq!(faction.stance_for(10), Stance::Friendly);
+        assert_eq!(faction.stance_for(11), Stance::Friendly);
+    }
+
+    #[test]
+    fn stances_toward_factions() {
+        let faction = faction(&[("dogs", Stance::Hostile), ("alley_cats", Stance::Hostile)]);
+        assert_eq!(faction.stance_toward("dogs"), Stance::Hostile);
+        assert_eq!(faction.stance_toward("rats"), Stance::Neutral);
+        // A stance listed toward itself is ignored; validation reports it.
+        assert_eq!(faction.stance_toward("alley_cats"), Stance::Friendly);
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{fmt, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +15,7 @@ pub enum EquipSlot {
     Trinket2,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum GadgetSlot {
     Edge,
     Handle,
@@ -39,7 +39,7 @@ pub struct StackableDef {
     pub max: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum StatId {
     Damage,
     Cooldown,
@@ -47,20 +47,44 @@ pub enum StatId {
     Durability,
 }
 
+/// How a modifier's `factor` combines with a stat. All `Flat` modifiers apply
+/// first, then `AddPercent`, then `Multiply`; see [`crate::tinkering`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModifierOp {
+    /// Adds `factor` to the base value.
+    Flat,
+    /// Adds `factor` as a fraction of the value, summed with other percentages
+    /// (`0.2` is +20%).
+    AddPercent,
+    /// Multiplies the value by `factor`.
+    #[default]
+    Multiply,
+}
+
+impl ModifierOp {
+    fn is_multiply(&self) -> bool {
+        *self == ModifierOp::Multiply
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatModifier {
     pub stat: StatId,
     pub factor: f32,
+
+    #[serde(default, skip_serializing_if = "ModifierOp::is_multiply")]
+    pub op: ModifierOp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GadgetDef {
-    #[serde(default, alias = "slot", deserialize_with = "deserialize_gadget_slots")]
+    #[serde(default)]
     pub slots: Vec<GadgetSlot>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub modifiers: Vec<StatModifier>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub script: Option<PathBuf>,
 }
 
@@ -98,26 +122,262 @@ pub struct ItemDef {
     pub name: String,
     pub description: String,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub components: Vec<ItemComponent>,
 }
 
-fn deserialize_gadget_slots<'de, D>(deserializer: D) -> Result<Vec<GadgetSlot>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum SlotList {
-        One(GadgetSlot),
-        Many(Vec<GadgetSlot>),
+impl ItemDef {
+    pub fn stackable(&self) -> Option<&StackableDef> {
+        self.components.iter().find_map(|c| match c {
+            ItemComponent::Stackable(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    pub fn equipable(&self) -> Option<&EquipableDef> {
+        self.components.iter().find_map(|c| match c {
+            ItemComponent::Equipable(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    pub fn weapon(&self) -> Option<&WeaponDef> {
+        self.components.iter().find_map(|c| match c {
+            ItemComponent::Weapon(def) => Some(def),
+            _ => None,
+        })
     }
 
-    match SlotList::deserialize(deserializer)? {
-        SlotList::One(slot) => Ok(vec![slot]),
-        SlotList::Many(slots) => Ok(slots),
+    pub fn armor(&self) -> Option<&ArmorDef> {
+        self.components.iter().find_map(|c| match c {
+            ItemComponent::Armor(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    pub fn damageable(&self) -> Option<&DamageableDef> {
+        self.components.iter().find_map(|c| match c {
+            ItemComponent::Damageable(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    pub fn gadget(&self) -> Option<&GadgetDef> {
+        self.components.iter().find_map(|c| match c {
+            ItemComponent::Gadget(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    /// Every gadget host, one per slot kind.
+    pub fn gadget_hosts(&self) -> impl Iterator<Item = &GadgetHostDef> {
+        self.components.iter().filter_map(|c| match c {
+            ItemComponent::GadgetHost(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    pub fn has(&self, kind: ComponentKind) -> bool {
+        self.count(kind) > 0
+    }
+
+    pub fn count(&self, kind: ComponentKind) -> usize {
+        self.components.iter().filter(|c| c.kind() == kind).count()
+    }
+
+    /// Checks the components against `rules` and their own values. Each
+    /// returned message describes one problem; empty means the item is valid.
+    pub fn check_components<'a>(
+        &self,
+        rules: impl IntoIterator<Item = &'a ComponentRule>,
+    ) -> Vec<String> {
+        let mut problems: Vec<String> = rules.into_iter().filter_map(|r| r.check(self)).collect();
+
+        let mut host_slots = Vec::new();
+        for component in &self.components {
+            match component {
+                ItemComponent::Stackable(def) if def.max == 0 => {
+                    problems.push("Stackable max must be at least 1".to_owned());
+                }
+                ItemComponent::Gadget(def) if def.slots.is_empty() => {
+                    problems.push("Gadget must fit at least one slot".to_owned());
+                }
+                ItemComponent::GadgetHost(def) => {
+                    if def.count == 0 {
+                        problems.push(format!("GadgetHost for {:?} has a count of 0", def.slot));
+                    }
+                    if host_slots.contains(&def.slot) {
+                        problems.push(format!(
+                            "more than one GadgetHost for {:?}; raise its count instead",
+                            def.slot
+                        ));
+                    }
+                    host_slots.push(def.slot);
+                }
+                _ => {}
+            }
+        }
+
+        problems
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ComponentKind {
+    Stackable,
+    Equipable,
+    Weapon,
+    Armor,
+    Damageable,
+    Gadget,
+    GadgetHost,
+}
+
+impl fmt::Display for ComponentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl ItemComponent {
+    pub fn kind(&self) -> ComponentKind {
+        match self {
+            ItemComponent::Stackable(_) => ComponentKind::Stackable,
+            ItemComponent::Equipable(_) => ComponentKind::Equipable,
+            ItemComponent::Weapon(_) => ComponentKind::Weapon,
+            ItemComponent::Armor(_) => ComponentKind::Armor,
+            ItemComponent::Damageable(_) => ComponentKind::Damageable,
+            ItemComponent::Gadget(_) => ComponentKind::Gadget,
+            ItemComponent::GadgetHost(_) => ComponentKind::GadgetHost,
+        }
+    }
+}
+
+/// A constraint on which components an item may combine.
+///
+/// The game enforces [`BUILTIN_COMPONENT_RULES`]; mods add their own with a
+/// `ComponentRule(...)` data entry, e.g. `ComponentRule(Requires(Armor, Damageable))`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComponentRule {
+    /// At most one component of this kind per item.
+    Unique(ComponentKind),
+    /// Items with the first kind must also have the second.
+    Requires(ComponentKind, ComponentKind),
+    /// Items may not have both kinds.
+    Excludes(ComponentKind, ComponentKind),
+}
+
+pub const BUILTIN_COMPONENT_RULES: &[ComponentRule] = &[
+    ComponentRule::Unique(ComponentKind::Stackable),
+    ComponentRule::Unique(ComponentKind::Equipable),
+    ComponentRule::Unique(ComponentKind::Weapon),
+    ComponentRule::Unique(ComponentKind::Armor),
+    ComponentRule::Unique(ComponentKind::Damageable),
+    ComponentRule::Unique(ComponentKind::Gadget),
+    ComponentRule::Requires(ComponentKind::Weapon, ComponentKind::Equipable),
+    ComponentRule::Requires(ComponentKind::Armor, ComponentKind::Equipable),
+    ComponentRule::Excludes(ComponentKind::Stackable, ComponentKind::Damageable),
+];
+
+impl ComponentRule {
+    /// Describes how `item` breaks this rule, if it does.
+    pub fn check(&self, item: &ItemDef) -> Option<String> {
+        match *self {
+            ComponentRule::Unique(kind) => {
+                let count = item.count(kind);
+                (count > 1).then(|| format!("has {count} {kind} components, at most 1 is allowed"))
+            }
+            ComponentRule::Requires(kind, required) => (item.has(kind) && !item.has(required))
+                .then(|| format!("{kind} requires {required}")),
+            ComponentRule::Excludes(a, b) => {
+                (item.has(a) && item.has(b)).then(|| format!("{a} and {b} cannot be combined"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        loader::parse_data_file,
+        registry::{ContentRegistry, DefSource},
+        schema::document::ContentDoc,
+    };
+
+    fn item(components: &str) -> ItemDef {
+        let text =
+            format!(r#"Item(id: "a", name: "A", description: "", components: [{components}])"#);
+        match parse_data_file(&text, "a.ron", None)
+            .unwrap()
+            .into_docs()
+            .remove(0)
+        {
+            ContentDoc::Item(item) => item,
+            other => panic!("expected an item, got {other:?}"),
+        }
+    }
+
+    fn problems(components: &str) -> Vec<String> {
+        item(components).check_components(BUILTIN_COMPONENT_RULES)
+    }
+
+    #[test]
+    fn valid_components_pass() {
+        let sword = "Equipable(slot: WeaponMain), Weapon(damage: 4, cooldown: 1), \
+                     Damageable(durability: 50)";
+        assert_eq!(problems(sword), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn unique_components_appear_once() {
+        assert_eq!(
+            problems("Stackable(max: 5), Stackable(max: 10)"),
+            ["has 2 Stackable components, at most 1 is allowed"]
+        );
+    }
+
+    #[test]
+    fn required_components_must_be_present() {
+        assert_eq!(problems("Armor(defense: 2)"), ["Armor requires Equipable"]);
+    }
+
+    #[test]
+    fn excluded_components_cannot_be_combined() {
+        assert_eq!(
+            problems("Stackable(max: 5), Damageable(durability: 10)"),
+            ["Stackable and Damageable cannot be combined"]
+        );
+    }
+
+    #[test]
+    fn mods_can_add_rules() {
+        let mut registry = ContentRegistry::default();
+        let rule = parse_data_file(
+            "ComponentRule(Requires(Armor, Damageable))",
+            "rules.ron",
+            None,
+        )
+        .unwrap()
+        .into_docs()
+        .remove(0);
+        let source = DefSource {
+            mod_id: "base".to_owned(),
+            file: PathBuf::from("data/rules.ron"),
+        };
+        registry.apply(rule, source).unwrap();
+
+        let vest = item("Equipable(slot: Body), Armor(defense: 2)");
+        assert_eq!(
+            vest.check_components(BUILTIN_COMPONENT_RULES),
+            [] as [&str; 0]
+        );
+        assert_eq!(
+            vest.check_components(registry.component_rules()),
+            ["Armor requires Damageable"]
+        );
     }
 }
@@ -1,16 +1,68 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+/// One outcome of a loot table roll.
+///
+/// An entry drops `item`, rolls the loot table `table`, or, with neither set,
+/// drops nothing (a weighted "empty" outcome).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LootEntryDef {
+    #[serde(default = "one")]
     pub weight: u32,
-    pub item: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+
+    /// How many of `item` to drop, or how many times to roll `table`.
+    #[serde(default = "one")]
     pub min: u32,
+    #[serde(default = "one")]
     pub max: u32,
+
+    /// Dropped every time the table is rolled, outside the weighted draws.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub guaranteed: bool,
+
+    /// The entry only takes part when all of these hold.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<LootCondition>,
+}
+
+/// Names the entry in messages: `item 'x'`, `table 'y'` or `nothing`.
+impl fmt::Display for LootEntryDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.item, &self.table) {
+            (Some(item), _) => write!(f, "item '{item}'"),
+            (None, Some(table)) => write!(f, "table '{table}'"),
+            (None, None) => f.write_str("nothing"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LootCondition {
+    /// The zone being looted carries this tag.
+    ZoneTag(String),
+    MinDifficulty(u32),
+    MaxDifficulty(u32),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "LootTable")]
 pub struct LootTableDef {
     pub id: String,
     pub rolls: u32,
     pub entries: Vec<LootEntryDef>,
 }
+
+fn one() -> u32 {
+    1
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// How a noise fades between its source and the edge of its radius.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Falloff {
+    /// Full loudness up to the radius, nothing beyond.
+    Constant,
+    Linear,
+    /// Fades with the square of the distance, so it drops off quickly near
+    /// the source.
+    Quadratic,
+}
+
+/// A kind of noise, such as a wet hit or a footstep, named by
+/// [`NoiseDef::kind`](super::recipes::NoiseDef) and by `emit_noise` in scripts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "NoiseKind")]
+pub struct NoiseKindDef {
+    pub id: String,
+
+    /// Radius in world units that a noise of loudness 1 carries; it scales
+    /// with loudness.
+    pub base_radius: f32,
+
+    #[serde(default = "linear")]
+    pub falloff: Falloff,
+
+    /// Fraction of loudness left after passing through each wall, from 0
+    /// (walls block it) to 1 (walls do nothing).
+    pub wall_attenuation: f32,
+
+    /// Seconds an AI that heard the noise keeps investigating it.
+    pub ai_memory_s: f32,
+
+    /// Sound the client plays, as a path under the mod's `assets/` folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_cue: Option<PathBuf>,
+}
+
+impl NoiseKindDef {
+    /// Loudness heard `distance` world units from a noise of `loudness`, with
+    /// `walls` walls in between. Zero outside the noise's radius.
+    pub fn loudness_at(&self, loudness: f32, distance: f32, walls: u32) -> f32 {
+        let radius = self.base_radius * loudness;
+        if radius <= 0.0 || distance > radius {
+            return 0.0;
+        }
+        let t = distance / radius;
+        let falloff = match self.falloff {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => 1.0 - t,
+            Falloff::Quadratic => (1.0 - t) * (1.0 - t),
+        };
+        let walls = i32::try_from(walls).unwrap_or(i32::MAX);
+        loudness * falloff * self.wall_attenuation.powi(walls)
+    }
+}
+
+fn linear() -> Falloff {
+    Falloff::Linear
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(falloff: Falloff) -> NoiseKindDef {
+        NoiseKindDef {
+            id: "thud".to_owned(),
+            base_radius: 10.0,
+            falloff,
+            wall_attenuation: 0.5,
+            ai_memory_s: 5.0,
+            sound_cue: None,
+        }
+    }
+
+    #[test]
+    fn each_falloff_fades_over_the_radius() {
+        // Loudness 2 carries 20 units; 5 units out is a quarter of the way.
+        assert_eq!(kind(Falloff::Constant).loudness_at(2.0, 5.0, 0), 2.0);
+        assert_eq!(kind(Falloff::Linear).loudness_at(2.0, 5.0, 0), 1.5);
+        assert_eq!(kind(Falloff::Quadratic).loudness_at(2.0, 5.0, 0), 1.125);
+
+        assert_eq!(kind(Falloff::Constant).loudness_at(2.0, 20.0, 0), 2.0);
+        assert_eq!(kind(Falloff::Linear).loudness_at(2.0, 20.0, 0), 0.0);
+    }
+
+    #[test]
+    fn nothing_is_heard_beyond_the_radius() {
+        for falloff in [Falloff::Constant, Falloff::Linear, Falloff::Quadratic] {
+            assert_eq!(kind(falloff).loudness_at(2.0, 20.5, 0), 0.0);
+            assert_eq!(kind(falloff).loudness_at(0.0, 0.0, 0), 0.0);
+        }
+    }
+
+    #[test]
+    fn each_wall_attenuates_the_noise() {
+        assert_eq!(kind(Falloff::Constant).loudness_at(2.0, 5.0, 1), 1.0);
+        assert_eq!(kind(Falloff::Linear).loudness_at(2.0, 5.0, 2), 0.375);
+        assert_eq!(kind(Falloff::Quadratic).loudness_at(2.0, 5.0, 3), 0.140625);
+
+        let mut solid = kind(Falloff::Constant);
+        solid.wall_attenuation = 0.0;
+        assert_eq!(solid.loudness_at(2.0, 5.0, 0), 2.0);
+        assert_eq!(solid.loudness_at(2.0, 5.0, 1), 0.0);
+    }
+}
@@ -0,0 +1,452 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    benches::BenchDef,
+    dialogue::{DialogueDef, DialogueNode},
+    enemies::EnemyDef,
+    factions::{FactionDef, ReputationThresholds, Stance, StanceEffects},
+    items::{ItemComponent, ItemDef, StatModifier, WeaponDef},
+    loot::{LootEntryDef, LootTableDef},
+    noise::{Falloff, NoiseKindDef},
+    quests::{QuestDef, QuestObjective, QuestRewards},
+    recipes::{ItemStackDef, NoiseDef, RecipeDef, RecipeRequirements},
+    status::StatusDef,
+    tags::TagDef,
+};
+
+/// A partial update applied on top of an existing definition.
+///
+/// Across all patches, `Option` fields replace the target's value when set,
+/// and plain list fields are appended to the target's list.
+pub trait Patch {
+    type Target;
+
+    fn id(&self) -> &str;
+
+    fn apply(&self, target: &mut Self::Target);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PatchDef {
+    Item(ItemPatch),
+    Bench(BenchPatch),
+    Recipe(RecipePatch),
+    LootTable(LootTablePatch),
+    Status(StatusPatch),
+    Enemy(EnemyPatch),
+    NoiseKind(NoiseKindPatch),
+    Faction(FactionPatch),
+    Tag(TagPatch),
+    Dialogue(DialoguePatch),
+    Quest(QuestPatch),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Item")]
+pub struct ItemPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove_tags: Vec<String>,
+
+    /// Replaces the whole component list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<ItemComponent>>,
+}
+
+impl Patch for ItemPatch {
+    type Target = ItemDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut ItemDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.description, &self.description);
+        append(&mut target.tags, &self.tags);
+        target.tags.retain(|tag| !self.remove_tags.contains(tag));
+        replace(&mut target.components, &self.components);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Bench")]
+pub struct BenchPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub craft_speed_mult: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noise_mult: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_recipe_tags: Vec<String>,
+}
+
+impl Patch for BenchPatch {
+    type Target = BenchDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut BenchDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.craft_speed_mult, &self.craft_speed_mult);
+        replace(&mut target.noise_mult, &self.noise_mult);
+        append(&mut target.allowed_recipe_tags, &self.allowed_recipe_tags);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Recipe")]
+pub struct RecipePatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bench_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_s: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipe_tags: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingredients: Option<Vec<ItemStackDef>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<ItemStackDef>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noise: Option<NoiseDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requirements: Option<RecipeRequirements>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<PathBuf>,
+}
+
+impl Patch for RecipePatch {
+    type Target = RecipeDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut RecipeDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.bench_id, &self.bench_id);
+        replace(&mut target.time_s, &self.time_s);
+        append(&mut target.recipe_tags, &self.recipe_tags);
+        replace(&mut target.ingredients, &self.ingredients);
+        replace(&mut target.outputs, &self.outputs);
+        if self.noise.is_some() {
+            target.noise.clone_from(&self.noise);
+        }
+        if self.requirements.is_some() {
+            target.requirements.clone_from(&self.requirements);
+        }
+        if self.script.is_some() {
+            target.script.clone_from(&self.script);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "LootTable")]
+pub struct LootTablePatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rolls: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<LootEntryDef>,
+}
+
+impl Patch for LootTablePatch {
+    type Target = LootTableDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut LootTableDef) {
+        replace(&mut target.rolls, &self.rolls);
+        target.entries.extend(self.entries.iter().cloned());
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Status")]
+pub struct StatusPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_stacks: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_interval_s: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub damage_per_tick: Option<f32>,
+
+    /// Replaces the whole modifier list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modifiers: Option<Vec<StatModifier>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noise: Option<NoiseDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<PathBuf>,
+}
+
+impl Patch for StatusPatch {
+    type Target = StatusDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut StatusDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.description, &self.description);
+        replace(&mut target.max_stacks, &self.max_stacks);
+        replace(&mut target.tick_interval_s, &self.tick_interval_s);
+        replace(&mut target.damage_per_tick, &self.damage_per_tick);
+        replace(&mut target.modifiers, &self.modifiers);
+        if self.noise.is_some() {
+            target.noise.clone_from(&self.noise);
+        }
+        if self.script.is_some() {
+            target.script.clone_from(&self.script);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Enemy")]
+pub struct EnemyPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub move_speed: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chase_speed: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hearing_threshold: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vision_range: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vision_cone_deg: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attack: Option<WeaponDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drops: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sprite: Option<PathBuf>,
+}
+
+impl Patch for EnemyPatch {
+    type Target = EnemyDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut EnemyDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.health, &self.health);
+        replace(&mut target.move_speed, &self.move_speed);
+        replace(&mut target.chase_speed, &self.chase_speed);
+        replace(&mut target.hearing_threshold, &self.hearing_threshold);
+        replace(&mut target.vision_range, &self.vision_range);
+        replace(&mut target.vision_cone_deg, &self.vision_cone_deg);
+        replace(&mut target.attack, &self.attack);
+        if self.drops.is_some() {
+            target.drops.clone_from(&self.drops);
+        }
+        replace(&mut target.behavior, &self.behavior);
+        replace(&mut target.sprite, &self.sprite);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "NoiseKind")]
+pub struct NoiseKindPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_radius: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub falloff: Option<Falloff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wall_attenuation: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_memory_s: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sound_cue: Option<PathBuf>,
+}
+
+impl Patch for NoiseKindPatch {
+    type Target = NoiseKindDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut NoiseKindDef) {
+        replace(&mut target.base_radius, &self.base_radius);
+        replace(&mut target.falloff, &self.falloff);
+        replace(&mut target.wall_attenuation, &self.wall_attenuation);
+        replace(&mut target.ai_memory_s, &self.ai_memory_s);
+        if self.sound_cue.is_some() {
+            target.sound_cue.clone_from(&self.sound_cue);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Faction")]
+pub struct FactionPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_stance: Option<Stance>,
+
+    /// Sets the stance toward each listed faction, keeping the others.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stances: BTreeMap<String, Stance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<ReputationThresholds>,
+
+    /// Replaces the effects of each listed stance, keeping the others.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rewards: BTreeMap<Stance, StanceEffects>,
+}
+
+impl Patch for FactionPatch {
+    type Target = FactionDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut FactionDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.default_stance, &self.default_stance);
+        target.stances.extend(self.stances.clone());
+        replace(&mut target.thresholds, &self.thresholds);
+        target.rewards.extend(self.rewards.clone());
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Tag")]
+pub struct TagPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+impl Patch for TagPatch {
+    type Target = TagDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut TagDef) {
+        if self.parent.is_some() {
+            target.parent.clone_from(&self.parent);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Dialogue")]
+pub struct DialoguePatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+
+    /// Replaces the target's node with the same id, or adds the node.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl Patch for DialoguePatch {
+    type Target = DialogueDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut DialogueDef) {
+        replace(&mut target.start, &self.start);
+        for node in &self.nodes {
+            match target.nodes.iter_mut().find(|old| old.id == node.id) {
+                Some(old) => old.clone_from(node),
+                None => target.nodes.push(node.clone()),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Quest")]
+pub struct QuestPatch {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Replaces the whole objective list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objectives: Option<Vec<QuestObjective>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewards: Option<QuestRewards>,
+}
+
+impl Patch for QuestPatch {
+    type Target = QuestDef;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn apply(&self, target: &mut QuestDef) {
+        replace(&mut target.name, &self.name);
+        replace(&mut target.description, &self.description);
+        replace(&mut target.objectives, &self.objectives);
+        replace(&mut target.rewards, &self.rewards);
+    }
+}
+
+fn replace<T: Clone>(target: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        target.clone_from(value);
+    }
+}
+
+fn append(target: &mut Vec<String>, values: &[String]) {
+    for value in values {
+        if !target.contains(value) {
+            target.push(value.clone());
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::recipes::ItemStackDef;
+
+/// One thing a quest asks for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum QuestObjective {
+    Collect {
+        item: String,
+        count: u32,
+    },
+    Craft {
+        recipe: String,
+        count: u32,
+    },
+    /// A zone of the map, by the name it has in the level data.
+    ReachZone(String),
+    Defeat {
+        enemy: String,
+        count: u32,
+    },
+}
+
+/// Given when a quest is completed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestRewards {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemStackDef>,
+
+    /// Reputation change per faction id; negative values are penalties.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub reputation: BTreeMap<String, i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Quest")]
+pub struct QuestDef {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+
+    /// Completed in order.
+    pub objectives: Vec<QuestObjective>,
+
+    #[serde(default)]
+    pub rewards: QuestRewards,
+}
@@ -16,25 +16,30 @@ pub struct NoiseDef {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecipeRequirements {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub needs_equipped_item_tag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Recipe")]
 pub struct RecipeDef {
     pub id: String,
     pub name: String,
     pub bench_id: String,
     pub time_s: f32,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub recipe_tags: Vec<String>,
 
     pub ingredients: Vec<ItemStackDef>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub outputs: Vec<ItemStackDef>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub noise: Option<NoiseDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub requirements: Option<RecipeRequirements>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub script: Option<PathBuf>,
 }
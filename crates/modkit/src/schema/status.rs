@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{items::StatModifier, recipes::NoiseDef};
+
+/// A status effect such as bleed or poison, applied to an entity with
+/// `apply_status(entity_id, status_id, stacks, duration_s)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Status")]
+pub struct StatusDef {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+
+    /// Stacks applied beyond this are dropped.
+    #[serde(default = "one")]
+    pub max_stacks: u32,
+
+    /// Seconds between ticks while the status is active.
+    #[serde(default = "one_second")]
+    pub tick_interval_s: f32,
+
+    /// Damage dealt on every tick, per stack.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damage_per_tick: f32,
+
+    /// Applied to the affected entity's stats while the status is active,
+    /// once however many stacks it has.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<StatModifier>,
+
+    /// Noise the affected entity emits on every tick.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise: Option<NoiseDef>,
+
+    /// Script defining any of `on_status_apply`, `on_status_tick` and
+    /// `on_status_expire`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<PathBuf>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn one_second() -> f32 {
+    1.0
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
+}
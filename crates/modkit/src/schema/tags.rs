@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// A tag items, recipes and benches may carry.
+///
+/// A tag with a `parent` also counts as that parent wherever tags are
+/// matched, so a bench allowing `weapon` accepts a `weapon.melee` recipe.
+/// Without an explicit `parent`, a dotted id sits under its prefix:
+/// `weapon.melee` under `weapon`, resolved like any other tag reference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "Tag")]
+pub struct TagDef {
+    pub id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
@@ -0,0 +1,256 @@
+use thiserror::Error;
+
+use crate::{
+    registry::ContentRegistry,
+    schema::{benches::BenchDef, recipes::RecipeDef},
+};
+
+/// The pattern matching every tag.
+pub const WILDCARD: &str = "*";
+
+/// Splits `weapon.*` into `("weapon", true)`; any other pattern is returned
+/// whole with `false`.
+pub fn split_pattern(pattern: &str) -> (&str, bool) {
+    match pattern.strip_suffix(".*") {
+        Some(tag) => (tag, true),
+        None => (pattern, false),
+    }
+}
+
+/// `tag`, then its parent, grandparent and so on. Stops at a tag that is not
+/// defined or that was already visited, so parent cycles end the walk.
+pub fn ancestors<'a>(registry: &'a ContentRegistry, tag: &'a str) -> Vec<&'a str> {
+    let mut chain = vec![tag];
+    let mut current = tag;
+    while let Some(parent) = registry
+        .tags
+        .get(current)
+        .and_then(|def| def.parent.as_deref())
+    {
+        if chain.contains(&parent) {
+            break;
+        }
+        chain.push(parent);
+        current = parent;
+    }
+    chain
+}
+
+/// Whether `tag` matches `pattern`:
+/// - `*` matches every tag
+/// - `weapon` matches `weapon` and every tag under it
+/// - `weapon.*` matches only the tags under `weapon`
+pub fn tag_matches(registry: &ContentRegistry, pattern: &str, tag: &str) -> bool {
+    if pattern == WILDCARD {
+        return true;
+    }
+    let (base, wildcard) = split_pattern(pattern);
+    ancestors(registry, tag)
+        .into_iter()
+        .skip(usize::from(wildcard))
+        .any(|ancestor| ancestor == base)
+}
+
+/// Whether any of `tags` matches `pattern`.
+pub fn any_tag_matches(registry: &ContentRegistry, pattern: &str, tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag_matches(registry, pattern, tag))
+}
+
+/// Tags of `recipe` that none of the bench's `allowed_recipe_tags` match. A
+/// bench without allowed tags accepts everything.
+pub fn disallowed_tags<'r>(
+    registry: &ContentRegistry,
+    bench: &BenchDef,
+    recipe: &'r RecipeDef,
+) -> Vec<&'r str> {
+    if bench.allowed_recipe_tags.is_empty() {
+        return Vec::new();
+    }
+    recipe
+        .recipe_tags
+        .iter()
+        .filter(|tag| {
+            !bench
+                .allowed_recipe_tags
+                .iter()
+                .any(|pattern| tag_matches(registry, pattern, tag))
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CraftError {
+    #[error("unknown recipe '{0}'")]
+    UnknownRecipe(String),
+
+    #[error("unknown bench '{0}'")]
+    UnknownBench(String),
+
+    #[error("recipe '{recipe}' is crafted at '{required}', not '{bench}'")]
+    WrongBench {
+        recipe: String,
+        bench: String,
+        required: String,
+    },
+
+    #[error("bench '{bench}' does not allow tags {} of recipe '{recipe}'", tags.join(", "))]
+    TagsNotAllowed {
+        recipe: String,
+        bench: String,
+        tags: Vec<String>,
+    },
+}
+
+/// Whether the recipe `recipe_id` can be crafted at the bench `bench_id`: the
+/// recipe must name that bench, and the bench must allow all of its tags.
+pub fn can_craft(
+    registry: &ContentRegistry,
+    recipe_id: &str,
+    bench_id: &str,
+) -> Result<(), CraftError> {
+    let recipe = registry
+        .recipes
+        .get(recipe_id)
+        .ok_or_else(|| CraftError::UnknownRecipe(recipe_id.to_owned()))?;
+    let bench = registry
+        .benches
+        .get(bench_id)
+        .ok_or_else(|| CraftError::UnknownBench(bench_id.to_owned()))?;
+
+    if recipe.bench_id != bench.id {
+        return Err(CraftError::WrongBench {
+            recipe: recipe.id.clone(),
+            bench: bench.id.clone(),
+            required: recipe.bench_id.clone(),
+        });
+    }
+
+    let disallowed = disallowed_tags(registry, bench, recipe);
+    if !disallowed.is_empty() {
+        return Err(CraftError::TagsNotAllowed {
+            recipe: recipe.id.clone(),
+            bench: bench.id.clone(),
+            tags: disallowed.into_iter().map(str::to_owned).collect(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        loader::parse_data_file,
+        registry::DefSource,
+        validate::{Severity, validate},
+    };
+
+    const TAGS: &str = r#"
+        Tag(id: "weapon"),
+        Tag(id: "blade", parent: "weapon"),
+        Tag(id: "dagger", parent: "blade"),
+        Tag(id: "armor"),
+    "#;
+
+    /// A registry holding [`TAGS`] and the entries in `docs`.
+    fn registry(docs: &str) -> ContentRegistry {
+        let text = format!("[{TAGS} {docs}]");
+        let mut registry = ContentRegistry::default();
+        for doc in parse_data_file(&text, "test.ron", None)
+            .unwrap()
+            .into_docs()
+        {
+            let source = DefSource {
+                mod_id: "base".to_owned(),
+                file: PathBuf::from("data/tags.ron"),
+            };
+            registry.apply(doc, source).unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn tags_match_their_ancestors() {
+        let registry = registry("");
+        assert_eq!(
+            ancestors(&registry, "dagger"),
+            ["dagger", "blade", "weapon"]
+        );
+        assert!(tag_matches(&registry, "weapon", "dagger"));
+        assert!(tag_matches(&registry, "blade", "dagger"));
+        assert!(tag_matches(&registry, "dagger", "dagger"));
+        assert!(!tag_matches(&registry, "dagger", "blade"));
+        assert!(!tag_matches(&registry, "armor", "dagger"));
+    }
+
+    #[test]
+    fn wildcard_patterns_match_children_or_everything() {
+        let registry = registry("");
+        assert!(tag_matches(&registry, "weapon.*", "blade"));
+        assert!(tag_matches(&registry, "weapon.*", "dagger"));
+        assert!(!tag_matches(&registry, "weapon.*", "weapon"));
+        assert!(tag_matches(&registry, "*", "armor"));
+        assert!(tag_matches(&registry, "*", "undefined"));
+    }
+
+    #[test]
+    fn benches_allow_recipes_by_tag() {
+        let registry = registry(
+            r#"
+            Bench(id: "smithy", name: "", craft_speed_mult: 1, noise_mult: 1, allowed_recipe_tags: ["weapon.*"]),
+            Bench(id: "table", name: "", craft_speed_mult: 1, noise_mult: 1),
+            Recipe(id: "shiv", name: "", bench_id: "smithy", time_s: 1, recipe_tags: ["dagger"], ingredients: []),
+            Recipe(id: "club", name: "", bench_id: "smithy", time_s: 1, recipe_tags: ["weapon"], ingredients: []),
+            Recipe(id: "vest", name: "", bench_id: "table", time_s: 1, recipe_tags: ["armor"], ingredients: []),
+            "#,
+        );
+        assert_eq!(can_craft(&registry, "shiv", "smithy"), Ok(()));
+        assert_eq!(can_craft(&registry, "vest", "table"), Ok(()));
+        assert_eq!(
+            can_craft(&registry, "club", "smithy"),
+            Err(CraftError::TagsNotAllowed {
+                recipe: "club".to_owned(),
+                bench: "smithy".to_owned(),
+                tags: vec!["weapon".to_owned()],
+            })
+        );
+        assert_eq!(
+            can_craft(&registry, "vest", "smithy"),
+            Err(CraftError::WrongBench {
+                recipe: "vest".to_owned(),
+                bench: "smithy".to_owned(),
+                required: "table".to_owned(),
+            })
+        );
+        assert_eq!(
+            can_craft(&registry, "sword", "smithy"),
+            Err(CraftError::UnknownRecipe("sword".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parent_cycles_are_rejected() {
+        let registry = registry(
+            r#"
+            Tag(id: "ouroboros", parent: "tail"),
+            Tag(id: "tail", parent: "ouroboros"),
+            "#,
+        );
+        // The walk stops instead of looping forever.
+        assert_eq!(ancestors(&registry, "tail"), ["tail", "ouroboros"]);
+
+        let cycles: Vec<String> = validate(&registry)
+            .into_iter()
+            .filter(|d| d.severity == Severity::Error && d.message.contains("cycle"))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            cycles,
+            ["tag parents form a cycle: ouroboros -> tail -> ouroboros"]
+        );
+    }
+}
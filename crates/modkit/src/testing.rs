@@ -0,0 +1,33 @@
+//! Fixtures shared by the unit tests.
+
+use std::{fs, path::PathBuf};
+
+use crate::migrate::CURRENT_MOD_API_VERSION;
+
+/// A fresh mods directory under the system temp dir holding `files`, given as
+/// paths relative to it. `name` keeps tests running in parallel apart.
+pub(crate) fn write_mods(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("modkit-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for (rel, text) in files {
+        let path = dir.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+    dir
+}
+
+/// A `mod.toml` for `mod_id` at the current API version, depending on any
+/// version of each of `dependencies`.
+pub(crate) fn manifest(mod_id: &str, dependencies: &[&str]) -> String {
+    let mut text = format!(
+        "mod_id = \"{mod_id}\"\nname = \"{mod_id}\"\nversion = \"1.0.0\"\n\
+         mod_api_version = {CURRENT_MOD_API_VERSION}\n"
+    );
+    for dependency in dependencies {
+        text.push_str(&format!(
+            "\n[[dependencies]]\nmod_id = \"{dependency}\"\nversion = \"*\"\n"
+        ));
+    }
+    text
+}
@@ -0,0 +1,371 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::schema::items::{GadgetSlot, ItemDef, ModifierOp, StatId};
+
+/// A gadget item placed into one of a host's slots.
+#[derive(Debug, Clone, Copy)]
+pub struct Attachment<'a> {
+    pub slot: GadgetSlot,
+    pub gadget: &'a ItemDef,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TinkerError {
+    #[error("'{item}' is not a gadget")]
+    NotAGadget { item: String },
+
+    #[error("'{gadget}' does not fit a {slot:?} slot")]
+    WrongSlot { gadget: String, slot: GadgetSlot },
+
+    #[error("'{host}' has no {slot:?} slot")]
+    NoSuchSlot { host: String, slot: GadgetSlot },
+
+    #[error("'{host}' has {count} {slot:?} slot(s), but {attached} gadgets were attached")]
+    SlotsFull {
+        host: String,
+        slot: GadgetSlot,
+        count: u8,
+        attached: usize,
+    },
+}
+
+/// The range a resolved stat is clamped to.
+pub fn stat_bounds(stat: StatId) -> RangeInclusive<f32> {
+    match stat {
+        StatId::Damage | StatId::Defense => 0.0..=f32::MAX,
+        // A zero cooldown would let a weapon fire every tick.
+        StatId::Cooldown => 0.05..=f32::MAX,
+        StatId::Durability => 1.0..=f32::MAX,
+    }
+}
+
+/// The stats an item has before any gadgets, from its components.
+pub fn base_stats(item: &ItemDef) -> BTreeMap<StatId, f32> {
+    let mut stats = BTreeMap::new();
+    if let Some(weapon) = item.weapon() {
+        stats.insert(StatId::Damage, weapon.damage);
+        stats.insert(StatId::Cooldown, weapon.cooldown);
+    }
+    if let Some(armor) = item.armor() {
+        stats.insert(StatId::Defense, armor.defense);
+    }
+    if let Some(damageable) = item.damageable() {
+        stats.insert(StatId::Durability, damageable.durability);
+    }
+    stats
+}
+
+/// One modifier that went into a stat, for showing where a value came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppliedModifier {
+    pub gadget_id: String,
+    pub slot: GadgetSlot,
+    pub stat: StatId,
+    pub op: ModifierOp,
+    pub factor: f32,
+}
+
+/// How one stat was computed:
+/// `value = clamp((base + flat) * (1 + add_percent) * multiply)`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatLine {
+    pub stat: StatId,
+    pub base: f32,
+    /// Sum of `Flat` factors.
+    pub flat: f32,
+    /// Sum of `AddPercent` factors, as a fraction.
+    pub add_percent: f32,
+    /// Product of `Multiply` factors.
+    pub multiply: f32,
+    /// The value before clamping to [`stat_bounds`].
+    pub unclamped: f32,
+    pub value: f32,
+    pub modifiers: Vec<AppliedModifier>,
+}
+
+impl StatLine {
+    pub fn is_clamped(&self) -> bool {
+        self.unclamped != self.value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatBreakdown {
+    /// Every stat the host has, in [`StatId`] order.
+    pub stats: Vec<StatLine>,
+    /// Modifiers for stats the host does not have; they have no effect.
+    pub unused: Vec<AppliedModifier>,
+}
+
+impl StatBreakdown {
+    pub fn get(&self, stat: StatId) -> Option<&StatLine> {
+        self.stats.iter().find(|line| line.stat == stat)
+    }
+
+    pub fn value(&self, stat: StatId) -> Option<f32> {
+        self.get(stat).map(|line| line.value)
+    }
+}
+
+/// Resolves the final stats of `host` with `attachments` in its slots.
+///
+/// Every attachment must be a gadget that fits the slot it is placed in, and
+/// no slot may hold more gadgets than the host's `GadgetHost` count. Gadget
+/// order does not matter: modifiers stack as described on [`StatLine`].
+pub fn resolve_stats(
+    host: &ItemDef,
+    attachments: &[Attachment<'_>],
+) -> Result<StatBreakdown, TinkerError> {
+    check_attachments(host, attachments)?;
+
+    let mut lines: Vec<StatLine> = base_stats(host)
+        .into_iter()
+        .map(|(stat, base)| StatLine {
+            stat,
+            base,
+            flat: 0.0,
+            add_percent: 0.0,
+            multiply: 1.0,
+            unclamped: base,
+            value: base,
+            modifiers: Vec::new(),
+        })
+        .collect();
+    let mut unused = Vec::new();
+
+    for attachment in attachments {
+        let Some(gadget) = attachment.gadget.gadget() else {
+            continue;
+        };
+        for modifier in &gadget.modifiers {
+            let applied = AppliedModifier {
+                gadget_id: attachment.gadget.id.clone(),
+                slot: attachment.slot,
+                stat: modifier.stat,
+                op: modifier.op,
+                factor: modifier.factor,
+            };
+            let Some(line) = lines.iter_mut().find(|line| line.stat == modifier.stat) else {
+                unused.push(applied);
+                continue;
+            };
+
+            match modifier.op {
+                ModifierOp::Flat => line.flat += modifier.factor,
+                ModifierOp::AddPercent => line.add_percent += modifier.factor,
+                ModifierOp::Multiply => line.multiply *= modifier.factor,
+            }
+            line.modifiers.push(applied);
+        }
+    }
+
+    for line in &mut lines {
+        let bounds = stat_bounds(line.stat);
+        line.unclamped = (line.base + line.flat) * (1.0 + line.add_percent) * line.multiply;
+        line.value = line.unclamped.clamp(*bounds.start(), *bounds.end());
+    }
+
+    Ok(StatBreakdown {
+        stats: lines,
+        unused,
+    })
+}
+
+fn check_attachments(host: &ItemDef, attachments: &[Attachment<'_>]) -> Result<(), TinkerError> {
+    let mut attached: BTreeMap<GadgetSlot, usize> = BTreeMap::new();
+
+    for attachment in attachments {
+        let item = attachment.gadget;
+        let Some(gadget) = item.gadget() else {
+            return Err(TinkerError::NotAGadget {
+                item: item.id.clone(),
+            });
+        };
+        if !gadget.slots.contains(&attachment.slot) {
+            return Err(TinkerError::WrongSlot {
+                gadget: item.id.clone(),
+                slot: attachment.slot,
+            });
+        }
+        *attached.entry(attachment.slot).or_default() += 1;
+    }
+
+    for (slot, attached) in attached {
+        let Some(gadget_host) = host.gadget_hosts().find(|h| h.slot == slot) else {
+            return Err(TinkerError::NoSuchSlot {
+                host: host.id.clone(),
+                slot,
+            });
+        };
+        if attached > usize::from(gadget_host.count) {
+            return Err(TinkerError::SlotsFull {
+                host: host.id.clone(),
+                slot,
+                count: gadget_host.count,
+                attached,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{loader::parse_data_file, schema::document::ContentDoc};
+
+    fn item(text: &str) -> ItemDef {
+        let docs = parse_data_file(text, "test.ron", None).unwrap().into_docs();
+        match docs.into_iter().next() {
+            Some(ContentDoc::Item(item)) => item,
+            other => panic!("expected an item, got {other:?}"),
+        }
+    }
+
+    fn rusty_nail_claws() -> ItemDef {
+        item(include_str!(
+            "../../../mods/base/data/items/rusty_nail_claws.ron"
+        ))
+    }
+
+    fn glass_shard() -> ItemDef {
+        item(include_str!(
+            "../../../mods/base/data/items/glass_shard.ron"
+        ))
+    }
+
+    fn gadget(id: &str, slot: &str, modifiers: &str) -> ItemDef {
+        item(&format!(
+            r#"Item(id: "{id}", name: "", description: "", components: [Gadget(slots: [{slot}], modifiers: [{modifiers}])])"#
+        ))
+    }
+
+    fn attach(slot: GadgetSlot, gadget: &ItemDef) -> Attachment<'_> {
+        Attachment { slot, gadget }
+    }
+
+    fn assert_close(actual: Option<f32>, expected: f32) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn glass_shard_sharpens_rusty_nail_claws() {
+        let (claws, shard) = (rusty_nail_claws(), glass_shard());
+        let stats = resolve_stats(&claws, &[attach(GadgetSlot::Edge, &shard)]).unwrap();
+
+        assert_close(stats.value(StatId::Damage), 3.0);
+        assert_close(stats.value(StatId::Cooldown), 0.22);
+        assert_close(stats.value(StatId::Durability), 50.0);
+        assert_eq!(stats.get(StatId::Damage).unwrap().modifiers.len(), 1);
+        assert!(stats.unused.is_empty());
+    }
+
+    #[test]
+    fn flat_then_add_percent_then_multiply() {
+        let claws = rusty_nail_claws();
+        let tuned = gadget(
+            "tuned",
+            "Edge",
+            "(stat: Damage, op: Multiply, factor: 2), \
+             (stat: Damage, op: AddPercent, factor: 0.5), \
+             (stat: Damage, op: Flat, factor: 2)",
+        );
+        let stats = resolve_stats(&claws, &[attach(GadgetSlot::Edge, &tuned)]).unwrap();
+
+        // (2 + 2) * (1 + 0.5) * 2, whatever order the modifiers are listed in.
+        let damage = stats.get(StatId::Damage).unwrap();
+        assert_eq!(damage.flat, 2.0);
+        assert_eq!(damage.add_percent, 0.5);
+        assert_eq!(damage.multiply, 2.0);
+        assert_close(Some(damage.value), 12.0);
+    }
+
+    #[test]
+    fn cooldown_is_clamped_to_its_floor() {
+        let claws = rusty_nail_claws();
+        let hasty = gadget("hasty", "Edge", "(stat: Cooldown, factor: 0.1)");
+        let stats = resolve_stats(&claws, &[attach(GadgetSlot::Edge, &hasty)]).unwrap();
+
+        let cooldown = stats.get(StatId::Cooldown).unwrap();
+        assert_close(Some(cooldown.unclamped), 0.02);
+        assert_eq!(cooldown.value, *stat_bounds(StatId::Cooldown).start());
+        assert!(cooldown.is_clamped());
+    }
+
+    #[test]
+    fn modifiers_for_missing_stats_are_unused() {
+        let claws = rusty_nail_claws();
+        let plated = gadget("plated", "Edge", "(stat: Defense, op: Flat, factor: 3)");
+        let stats = resolve_stats(&claws, &[attach(GadgetSlot::Edge, &plated)]).unwrap();
+
+        assert_eq!(stats.value(StatId::Defense), None);
+        assert_eq!(stats.unused.len(), 1);
+        assert_eq!(stats.unused[0].gadget_id, "plated");
+    }
+
+    #[test]
+    fn gadget_must_fit_the_slot() {
+        let (claws, shard) = (rusty_nail_claws(), glass_shard());
+        let err = resolve_stats(&claws, &[attach(GadgetSlot::Handle, &shard)]).unwrap_err();
+        assert_eq!(
+            err,
+            TinkerError::WrongSlot {
+                gadget: "glass_shard".to_owned(),
+                slot: GadgetSlot::Handle,
+            }
+        );
+    }
+
+    #[test]
+    fn host_must_have_the_slot() {
+        let claws = rusty_nail_claws();
+        let grip = gadget("grip", "Handle", "");
+        let err = resolve_stats(&claws, &[attach(GadgetSlot::Handle, &grip)]).unwrap_err();
+        assert_eq!(
+            err,
+            TinkerError::NoSuchSlot {
+                host: "rusty_nail_claws".to_owned(),
+                slot: GadgetSlot::Handle,
+            }
+        );
+    }
+
+    #[test]
+    fn slots_hold_at_most_their_count() {
+        let (claws, shard) = (rusty_nail_claws(), glass_shard());
+        let attachments = [
+            attach(GadgetSlot::Edge, &shard),
+            attach(GadgetSlot::Edge, &shard),
+        ];
+        let err = resolve_stats(&claws, &attachments).unwrap_err();
+        assert_eq!(
+            err,
+            TinkerError::SlotsFull {
+                host: "rusty_nail_claws".to_owned(),
+                slot: GadgetSlot::Edge,
+                count: 1,
+                attached: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn only_gadgets_can_be_attached() {
+        let claws = rusty_nail_claws();
+        let err = resolve_stats(&claws, &[attach(GadgetSlot::Edge, &claws)]).unwrap_err();
+        assert_eq!(
+            err,
+            TinkerError::NotAGadget {
+                item: "rusty_nail_claws".to_owned(),
+            }
+        );
+    }
+}
@@ -0,0 +1,1149 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    hashing::canonical_path,
+    ids::{IdResolver, split_id},
+    lang::{
+        DEFAULT_LOCALE, LANG_DIR, bench_name_key, enemy_name_key, faction_name_key,
+        item_description_key, item_name_key, key_kind, quest_description_key, quest_name_key,
+        recipe_name_key, status_description_key, status_name_key, text_keys,
+    },
+    noise::{EMIT_NOISE, emit_noise_calls},
+    registry::{ContentRegistry, DefKind, Definition, RegistryEntry},
+    schema::{
+        dialogue::{DialogueCondition, DialogueDef, DialogueEffect, Speaker},
+        enemies::behavior_script_path,
+        factions::Stance,
+        items::{ComponentRule, ItemComponent},
+        quests::QuestObjective,
+        recipes::{ItemStackDef, NoiseDef},
+    },
+    tags::{WILDCARD, ancestors, disallowed_tags, split_pattern},
+};
+
+pub const SCRIPTS_DIR: &str = "scripts";
+pub const ASSETS_DIR: &str = "assets";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A problem found in loaded content, attributed to the file that last touched it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub mod_id: String,
+    pub file: PathBuf,
+    pub kind: Option<DefKind>,
+    pub def_id: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}/{}",
+            self.severity,
+            self.mod_id,
+            self.file.display()
+        )?;
+        if let (Some(kind), Some(id)) = (self.kind, &self.def_id) {
+            write!(f, " ({kind} '{id}')")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+/// Checks references between definitions and the values the loader cannot
+/// reject on its own. An empty result means the registry is consistent.
+pub fn validate(registry: &ContentRegistry) -> Vec<Diagnostic> {
+    let mut v = Validator {
+        registry,
+        diagnostics: Vec::new(),
+    };
+
+    v.component_rules();
+    v.items();
+    v.benches();
+    v.recipes();
+    v.loot_tables();
+    v.tags();
+    v.statuses();
+    v.enemies();
+    v.noise_kinds();
+    v.script_noise();
+    v.factions();
+    v.dialogues();
+    v.quests();
+    v.translations();
+
+    v.diagnostics
+}
+
+struct Validator<'a> {
+    registry: &'a ContentRegistry,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn report<T: Definition>(
+        &mut self,
+        severity: Severity,
+        entry: &RegistryEntry<T>,
+        message: String,
+    ) {
+        let source = entry.last_touched_by();
+        self.diagnostics.push(Diagnostic {
+            severity,
+            mod_id: source.mod_id.clone(),
+            file: source.file.clone(),
+            kind: Some(T::KIND),
+            def_id: Some(entry.def.id().to_owned()),
+            message,
+        });
+    }
+
+    fn error<T: Definition>(&mut self, entry: &RegistryEntry<T>, message: String) {
+        self.report(Severity::Error, entry, message);
+    }
+
+    fn warn<T: Definition>(&mut self, entry: &RegistryEntry<T>, message: String) {
+        self.report(Severity::Warning, entry, message);
+    }
+
+    fn component_rules(&mut self) {
+        for (rule, source) in &self.registry.component_rules {
+            let (ComponentRule::Requires(a, b) | ComponentRule::Excludes(a, b)) = rule else {
+                continue;
+            };
+            if a == b {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    mod_id: source.mod_id.clone(),
+                    file: source.file.clone(),
+                    kind: None,
+                    def_id: None,
+                    message: format!("component rule {rule:?} relates {a} to itself"),
+                });
+            }
+        }
+    }
+
+    fn items(&mut self) {
+        let registry = self.registry;
+        for entry in registry.items.entries() {
+            let item = &entry.def;
+            for problem in item.check_components(registry.component_rules()) {
+                self.error(entry, problem);
+            }
+            for tag in &item.tags {
+                self.check_tag(entry, "tags", tag);
+            }
+            for component in &item.components {
+                if let ItemComponent::Gadget(gadget) = component
+                    && let Some(script) = &gadget.script
+                {
+                    self.check_script(entry, script);
+                }
+            }
+        }
+    }
+
+    fn benches(&mut self) {
+        let registry = self.registry;
+        for entry in registry.benches.entries() {
+            let bench = &entry.def;
+            if bench.craft_speed_mult <= 0.0 {
+                self.error(
+                    entry,
+                    format!(
+                        "craft_speed_mult must be positive, got {}",
+                        bench.craft_speed_mult
+                    ),
+                );
+            }
+            if bench.noise_mult < 0.0 {
+                self.error(
+                    entry,
+                    format!("noise_mult must not be negative, got {}", bench.noise_mult),
+                );
+            }
+            for pattern in &bench.allowed_recipe_tags {
+                self.check_tag(entry, "allowed_recipe_tags", pattern);
+            }
+        }
+    }
+
+    fn recipes(&mut self) {
+        let registry = self.registry;
+        for entry in registry.recipes.entries() {
+            let recipe = &entry.def;
+
+            match registry.benches.get(&recipe.bench_id) {
+                None => self.error(entry, format!("unknown bench '{}'", recipe.bench_id)),
+                Some(bench) => {
+                    let disallowed = disallowed_tags(registry, bench, recipe);
+                    if !disallowed.is_empty() {
+                        self.error(
+                            entry,
+                            format!(
+                                "bench '{}' does not allow recipe tags {}",
+                                bench.id,
+                                disallowed.join(", ")
+                            ),
+                        );
+                    }
+                }
+            }
+
+            for tag in &recipe.recipe_tags {
+                self.check_tag(entry, "recipe_tags", tag);
+            }
+            if let Some(tag) = recipe
+                .requirements
+                .as_ref()
+                .and_then(|r| r.needs_equipped_item_tag.as_ref())
+            {
+                self.check_tag(entry, "needs_equipped_item_tag", tag);
+            }
+
+            if let Some(noise) = &recipe.noise {
+                self.check_noise(entry, noise);
+            }
+
+            if recipe.time_s < 0.0 {
+                self.error(
+                    entry,
+                    format!("time_s must not be negative, got {}", recipe.time_s),
+                );
+            }
+
+            for (field, stacks) in [
+                ("ingredients", &recipe.ingredients),
+                ("outputs", &recipe.outputs),
+            ] {
+                self.check_stacks(entry, field, stacks);
+            }
+
+            if recipe.outputs.is_empty() && recipe.script.is_none() {
+                self.warn(entry, "recipe has no outputs and no script".to_owned());
+            }
+
+            if let Some(script) = &recipe.script {
+                self.check_script(entry, script);
+            }
+        }
+    }
+
+    fn loot_tables(&mut self) {
+        let registry = self.registry;
+        for entry in registry.loot_tables.entries() {
+            let table = &entry.def;
+
+            if table.entries.is_empty() {
+                self.warn(entry, "loot table has no entries".to_owned());
+            }
+            if table.rolls == 0 {
+                self.warn(
+                    entry,
+                    "loot table has 0 rolls and never drops anything".to_owned(),
+                );
+            }
+
+            for loot in &table.entries {
+                match (&loot.item, &loot.table) {
+                    (Some(_), Some(_)) => {
+                        self.error(entry, format!("entry for {loot} also sets a table"));
+                    }
+                    (Some(item), None) if !registry.items.contains(item) => {
+                        self.error(entry, format!("entry references unknown item '{item}'"));
+                    }
+                    (None, Some(other)) if !registry.loot_tables.contains(other) => {
+                        self.error(
+                            entry,
+                            format!("entry references unknown loot table '{other}'"),
+                        );
+                    }
+                    _ => {}
+                }
+                if loot.weight == 0 && !loot.guaranteed {
+                    self.error(entry, format!("entry for {loot} has a weight of 0"));
+                }
+                if loot.min > loot.max {
+                    self.error(
+                        entry,
+                        format!(
+                            "entry for {loot} has min {} greater than max {}",
+                            loot.min, loot.max
+                        ),
+                    );
+                }
+            }
+
+            // Each cycle is reported once, on its smallest table id.
+            if let Some(cycle) = loot_table_cycle(registry, &table.id)
+                && cycle.iter().all(|id| *id >= table.id.as_str())
+            {
+                self.error(
+                    entry,
+                    format!(
+                        "loot tables roll each other in a cycle: {}",
+                        cycle.join(" -> ")
+                    ),
+                );
+            }
+        }
+    }
+
+    fn tags(&mut self) {
+        let registry = self.registry;
+        for entry in registry.tags.entries() {
+            let tag = &entry.def;
+            let Some(parent) = &tag.parent else {
+                continue;
+            };
+            if !registry.tags.contains(parent) {
+                self.error(entry, format!("unknown parent tag '{parent}'"));
+                continue;
+            }
+
+            // Each cycle is reported once, on its smallest tag id.
+            let chain = ancestors(registry, parent);
+            if let Some(end) = chain.iter().position(|id| *id == tag.id)
+                && chain[..end].iter().all(|id| *id > tag.id.as_str())
+            {
+                let cycle: Vec<&str> = [tag.id.as_str()]
+                    .into_iter()
+                    .chain(chain[..end].iter().copied())
+                    .chain([tag.id.as_str()])
+                    .collect();
+                self.error(
+                    entry,
+                    format!("tag parents form a cycle: {}", cycle.join(" -> ")),
+                );
+            }
+        }
+    }
+
+    fn statuses(&mut self) {
+        let registry = self.registry;
+        for entry in registry.statuses.entries() {
+            let status = &entry.def;
+            if status.max_stacks == 0 {
+                self.error(entry, "max_stacks must be at least 1".to_owned());
+            }
+            if status.tick_interval_s <= 0.0 {
+                self.error(
+                    entry,
+                    format!(
+                        "tick_interval_s must be positive, got {}",
+                        status.tick_interval_s
+                    ),
+                );
+            }
+            if status.damage_per_tick < 0.0 {
+                self.error(
+                    entry,
+                    format!(
+                        "damage_per_tick must not be negative, got {}",
+                        status.damage_per_tick
+                    ),
+                );
+            }
+            if let Some(noise) = &status.noise {
+                self.check_noise(entry, noise);
+            }
+            if status.damage_per_tick == 0.0
+                && status.modifiers.is_empty()
+                && status.noise.is_none()
+                && status.script.is_none()
+            {
+                self.warn(entry, "status has no effect".to_owned());
+            }
+            if let Some(script) = &status.script {
+                self.check_script(entry, script);
+            }
+        }
+    }
+
+    fn enemies(&mut self) {
+        let registry = self.registry;
+        for entry in registry.enemies.entries() {
+            let enemy = &entry.def;
+            if enemy.health <= 0.0 {
+                self.error(
+                    entry,
+                    format!("health must be positive, got {}", enemy.health),
+                );
+            }
+            for (field, value) in [
+                ("move_speed", enemy.move_speed),
+                ("chase_speed", enemy.chase_speed),
+                ("hearing_threshold", enemy.hearing_threshold),
+                ("vision_range", enemy.vision_range),
+                ("attack damage", enemy.attack.damage),
+            ] {
+                if value < 0.0 {
+                    self.error(entry, format!("{field} must not be negative, got {value}"));
+                }
+            }
+            if enemy.vision_cone_deg <= 0.0 || enemy.vision_cone_deg > 360.0 {
+                self.error(
+                    entry,
+                    format!(
+                        "vision_cone_deg must be in (0, 360], got {}",
+                        enemy.vision_cone_deg
+                    ),
+                );
+            }
+            if enemy.attack.cooldown <= 0.0 {
+                self.error(
+                    entry,
+                    format!(
+                        "attack cooldown must be positive, got {}",
+                        enemy.attack.cooldown
+                    ),
+                );
+            }
+            if enemy.chase_speed < enemy.move_speed {
+                self.warn(
+                    entry,
+                    format!(
+                        "chase_speed {} is slower than move_speed {}",
+                        enemy.chase_speed, enemy.move_speed
+                    ),
+                );
+            }
+            if let Some(table) = &enemy.drops
+                && !registry.loot_tables.contains(table)
+            {
+                self.error(
+                    entry,
+                    format!("drops references unknown loot table '{table}'"),
+                );
+            }
+
+            let behavior_found = split_id(&enemy.behavior).is_some_and(|(mod_id, local)| {
+                registry
+                    .loaded_mod(mod_id)
+                    .is_some_and(|m| m.fs.is_file(&behavior_script_path(local)))
+            });
+            if !behavior_found {
+                let (mod_id, local) = split_id(&enemy.behavior).unwrap_or(("?", &enemy.behavior));
+                self.error(
+                    entry,
+                    format!(
+                        "behavior '{}' has no script '{}' in mod '{mod_id}'",
+                        enemy.behavior,
+                        behavior_script_path(local)
+                    ),
+                );
+            }
+
+            self.check_asset(entry, &enemy.sprite);
+        }
+    }
+
+    fn noise_kinds(&mut self) {
+        let registry = self.registry;
+        for entry in registry.noise_kinds.entries() {
+            let kind = &entry.def;
+            if kind.base_radius <= 0.0 {
+                self.error(
+                    entry,
+                    format!("base_radius must be positive, got {}", kind.base_radius),
+                );
+            }
+            if !(0.0..=1.0).contains(&kind.wall_attenuation) {
+                self.error(
+                    entry,
+                    format!(
+                        "wall_attenuation must be between 0 and 1, got {}",
+                        kind.wall_attenuation
+                    ),
+                );
+            }
+            if kind.ai_memory_s < 0.0 {
+                self.error(
+                    entry,
+                    format!("ai_memory_s must not be negative, got {}", kind.ai_memory_s),
+                );
+            }
+            if let Some(cue) = &kind.sound_cue {
+                self.check_asset(entry, cue);
+            }
+        }
+    }
+
+    /// Reports `emit_noise` calls in any mod's scripts whose kind is not a
+    /// declared noise kind. Kinds resolve like ids in the mod's data.
+    fn script_noise(&mut self) {
+        let registry = self.registry;
+        for loaded in &registry.mods {
+            let mod_id = &loaded.manifest.mod_id;
+            let ids = IdResolver::new(registry, mod_id, []);
+            let Ok(files) = loaded.fs.files() else {
+                continue;
+            };
+            let scripts = files.iter().filter(|rel| {
+                rel.starts_with(&format!("{SCRIPTS_DIR}/")) && rel.ends_with(".rhai")
+            });
+            for rel in scripts {
+                let Ok(source) = loaded.fs.read_to_string(rel) else {
+                    continue;
+                };
+                for call in emit_noise_calls(&source) {
+                    let message = match ids.resolve(DefKind::NoiseKind, &call.kind) {
+                        Ok(kind) if registry.noise_kinds.contains(&kind) => continue,
+                        Ok(kind) => format!(
+                            "{EMIT_NOISE} on line {} uses unknown noise kind '{kind}'",
+                            call.line
+                        ),
+                        Err(err) => format!("{EMIT_NOISE} on line {}: {err}", call.line),
+                    };
+                    self.diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        mod_id: mod_id.clone(),
+                        file: PathBuf::from(rel),
+                        kind: None,
+                        def_id: None,
+                        message,
+                    });
+                }
+            }
+        }
+    }
+
+    fn factions(&mut self) {
+        let registry = self.registry;
+        for entry in registry.factions.entries() {
+            let faction = &entry.def;
+            let thresholds = faction.thresholds;
+            if thresholds.hostile >= thresholds.friendly {
+                self.error(
+                    entry,
+                    format!(
+                        "hostile threshold {} must be below friendly threshold {}",
+                        thresholds.hostile, thresholds.friendly
+                    ),
+                );
+            }
+
+            for (other, &stance) in &faction.stances {
+                if *other == faction.id {
+                    self.error(entry, "faction lists a stance toward itself".to_owned());
+                    continue;
+                }
+                let Some(other_def) = registry.factions.get(other) else {
+                    self.error(
+                        entry,
+                        format!("stances references unknown faction '{other}'"),
+                    );
+                    continue;
+                };
+                // Reported once, on the smaller id.
+                let back = other_def.stance_toward(&faction.id);
+                if faction.id < *other
+                    && matches!(
+                        (stance, back),
+                        (Stance::Hostile, Stance::Friendly) | (Stance::Friendly, Stance::Hostile)
+                    )
+                {
+                    self.warn(
+                        entry,
+                        format!("{stance:?} toward '{other}', which is {back:?} toward it"),
+                    );
+                }
+            }
+
+            for (stance, effects) in &faction.rewards {
+                self.check_stacks(entry, &format!("{stance:?} reward items"), &effects.items);
+            }
+        }
+    }
+
+    fn dialogues(&mut self) {
+        let registry = self.registry;
+        for entry in registry.dialogues.entries() {
+            let dialogue = &entry.def;
+
+            let mut node_ids = BTreeSet::new();
+            for node in &dialogue.nodes {
+                if !node_ids.insert(node.id.as_str()) {
+                    self.error(entry, format!("node '{}' is defined twice", node.id));
+                }
+            }
+            if dialogue.node(&dialogue.start).is_none() {
+                self.error(
+                    entry,
+                    format!("start node '{}' does not exist", dialogue.start),
+                );
+            } else {
+                let reachable = dialogue_reachable(dialogue);
+                for id in node_ids.difference(&reachable) {
+                    self.warn(entry, format!("node '{id}' can never be reached"));
+                }
+            }
+
+            for node in &dialogue.nodes {
+                if let Speaker::Npc(name) = &node.speaker {
+                    self.check_text_key(entry, name);
+                }
+                self.check_text_key(entry, &node.text);
+                for choice in &node.choices {
+                    self.check_text_key(entry, &choice.text);
+                    if let Some(next) = &choice.next
+                        && dialogue.node(next).is_none()
+                    {
+                        self.error(
+                            entry,
+                            format!(
+                                "node '{}' has a choice leading to unknown node '{next}'",
+                                node.id
+                            ),
+                        );
+                    }
+                    for condition in &choice.conditions {
+                        self.check_condition(entry, condition);
+                    }
+                    for effect in &choice.effects {
+                        self.check_effect(entry, effect);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_condition<T: Definition>(
+        &mut self,
+        entry: &RegistryEntry<T>,
+        condition: &DialogueCondition,
+    ) {
+        match condition {
+            DialogueCondition::HasItem(stack) => {
+                self.check_stacks(entry, "condition", std::slice::from_ref(stack));
+            }
+            DialogueCondition::Reputation { faction, .. } => {
+                self.check_faction(entry, "condition", faction);
+            }
+            DialogueCondition::QuestActive(quest) | DialogueCondition::QuestComplete(quest) => {
+                self.check_quest(entry, "condition", quest);
+            }
+        }
+    }
+
+    fn check_effect<T: Definition>(&mut self, entry: &RegistryEntry<T>, effect: &DialogueEffect) {
+        match effect {
+            DialogueEffect::GiveItem(stack) | DialogueEffect::TakeItem(stack) => {
+                self.check_stacks(entry, "effect", std::slice::from_ref(stack));
+            }
+            DialogueEffect::Reputation { faction, .. } => {
+                self.check_faction(entry, "effect", faction);
+            }
+            DialogueEffect::StartQuest(quest) => self.check_quest(entry, "effect", quest),
+        }
+    }
+
+    fn quests(&mut self) {
+        let registry = self.registry;
+        for entry in registry.quests.entries() {
+            let quest = &entry.def;
+            if quest.objectives.is_empty() {
+                self.error(entry, "quest has no objectives".to_owned());
+            }
+            for objective in &quest.objectives {
+                let (kind, id, count) = match objective {
+                    QuestObjective::Collect { item, count } => (DefKind::Item, item, count),
+                    QuestObjective::Craft { recipe, count } => (DefKind::Recipe, recipe, count),
+                    QuestObjective::Defeat { enemy, count } => (DefKind::Enemy, enemy, count),
+                    QuestObjective::ReachZone(zone) => {
+                        if zone.is_empty() {
+                            self.error(entry, "objective names an empty zone".to_owned());
+                        }
+                        continue;
+                    }
+                };
+                if !registry.contains(kind, id) {
+                    self.error(entry, format!("objective references unknown {kind} '{id}'"));
+                }
+                if *count == 0 {
+                    self.error(entry, format!("objective for '{id}' has a count of 0"));
+                }
+            }
+            self.check_stacks(entry, "reward items", &quest.rewards.items);
+            for faction in quest.rewards.reputation.keys() {
+                self.check_faction(entry, "reward reputation", faction);
+            }
+        }
+    }
+
+    fn check_faction<T: Definition>(&mut self, entry: &RegistryEntry<T>, field: &str, id: &str) {
+        if !self.registry.factions.contains(id) {
+            self.error(entry, format!("{field} references unknown faction '{id}'"));
+        }
+    }
+
+    fn check_quest<T: Definition>(&mut self, entry: &RegistryEntry<T>, field: &str, id: &str) {
+        if !self.registry.quests.contains(id) {
+            self.error(entry, format!("{field} references unknown quest '{id}'"));
+        }
+    }
+
+    /// Reports a key of text that only lives in lang files and is missing
+    /// from [`DEFAULT_LOCALE`], so it would show as the bare key.
+    fn check_text_key<T: Definition>(&mut self, entry: &RegistryEntry<T>, key: &str) {
+        if self
+            .registry
+            .translations
+            .get(DEFAULT_LOCALE, key)
+            .is_none()
+        {
+            self.error(
+                entry,
+                format!("text key '{key}' is missing from {LANG_DIR}/{DEFAULT_LOCALE}.ron"),
+            );
+        }
+    }
+
+    fn check_stacks<T: Definition>(
+        &mut self,
+        entry: &RegistryEntry<T>,
+        field: &str,
+        stacks: &[ItemStackDef],
+    ) {
+        for stack in stacks {
+            if !self.registry.items.contains(&stack.item) {
+                self.error(
+                    entry,
+                    format!("{field} references unknown item '{}'", stack.item),
+                );
+            }
+            if stack.count == 0 {
+                self.error(
+                    entry,
+                    format!("{field} entry for '{}' has a count of 0", stack.item),
+                );
+            }
+        }
+    }
+
+    fn check_noise<T: Definition>(&mut self, entry: &RegistryEntry<T>, noise: &NoiseDef) {
+        if noise.loudness < 0.0 {
+            self.error(
+                entry,
+                format!(
+                    "noise loudness must not be negative, got {}",
+                    noise.loudness
+                ),
+            );
+        }
+        if !self.registry.noise_kinds.contains(&noise.kind) {
+            self.error(
+                entry,
+                format!("noise references unknown noise kind '{}'", noise.kind),
+            );
+        }
+    }
+
+    /// Reports a tag, or the tag in a pattern like `weapon.*`, that is not defined.
+    fn check_tag<T: Definition>(&mut self, entry: &RegistryEntry<T>, field: &str, pattern: &str) {
+        if pattern == WILDCARD {
+            return;
+        }
+        let (tag, _) = split_pattern(pattern);
+        if !self.registry.tags.contains(tag) {
+            self.error(entry, format!("{field} references unknown tag '{tag}'"));
+        }
+    }
+
+    fn translations(&mut self) {
+        let registry = self.registry;
+        for entry in registry.items.entries() {
+            let id = &entry.def.id;
+            self.missing_text(entry, &[item_name_key(id), item_description_key(id)]);
+        }
+        for entry in registry.benches.entries() {
+            self.missing_text(entry, &[bench_name_key(&entry.def.id)]);
+        }
+        for entry in registry.recipes.entries() {
+            self.missing_text(entry, &[recipe_name_key(&entry.def.id)]);
+        }
+        for entry in registry.statuses.entries() {
+            let id = &entry.def.id;
+            self.missing_text(entry, &[status_name_key(id), status_description_key(id)]);
+        }
+        for entry in registry.enemies.entries() {
+            self.missing_text(entry, &[enemy_name_key(&entry.def.id)]);
+        }
+        for entry in registry.factions.entries() {
+            self.missing_text(entry, &[faction_name_key(&entry.def.id)]);
+        }
+        for entry in registry.quests.entries() {
+            let id = &entry.def.id;
+            self.missing_text(entry, &[quest_name_key(id), quest_description_key(id)]);
+        }
+
+        let known: BTreeSet<String> = text_keys(registry).into_iter().map(|k| k.key).collect();
+        for locale in registry.translations.locales() {
+            for (key, translation) in registry.translations.entries(locale) {
+                if key_kind(key).is_some() && !known.contains(key) {
+                    self.diagnostics.push(Diagnostic {
+                        severity: Severity::Warning,
+                        mod_id: translation.source.mod_id.clone(),
+                        file: translation.source.file.clone(),
+                        kind: None,
+                        def_id: None,
+                        message: format!("'{locale}' key '{key}' matches no definition"),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Warns once per locale that lacks any of `keys`. [`DEFAULT_LOCALE`] is
+    /// covered by the definition's own text.
+    fn missing_text<T: Definition>(&mut self, entry: &RegistryEntry<T>, keys: &[String]) {
+        let translations = &self.registry.translations;
+        for locale in translations.locales().filter(|&l| l != DEFAULT_LOCALE) {
+            let missing: Vec<&str> = keys
+                .iter()
+                .map(String::as_str)
+                .filter(|key| translations.get(locale, key).is_none())
+                .collect();
+            if !missing.is_empty() {
+                self.warn(
+                    entry,
+                    format!("no '{locale}' text for {}", missing.join(", ")),
+                );
+            }
+        }
+    }
+
+    fn check_script<T: Definition>(&mut self, entry: &RegistryEntry<T>, script: &Path) {
+        self.check_mod_file(entry, SCRIPTS_DIR, "script", script);
+    }
+
+    fn check_asset<T: Definition>(&mut self, entry: &RegistryEntry<T>, asset: &Path) {
+        self.check_mod_file(entry, ASSETS_DIR, "asset", asset);
+    }
+
+    /// Reports a `path` under `dir` that neither the mod defining the entry
+    /// nor the one that last touched it ships.
+    fn check_mod_file<T: Definition>(
+        &mut self,
+        entry: &RegistryEntry<T>,
+        dir: &str,
+        what: &str,
+        path: &Path,
+    ) {
+        let Ok(rel) = canonical_path(path) else {
+            self.error(
+                entry,
+                format!(
+                    "{what} path '{}' must be relative to {dir}/ and stay inside it",
+                    path.display()
+                ),
+            );
+            return;
+        };
+
+        let found = [entry.last_touched_by(), entry.defined_by()]
+            .into_iter()
+            .filter_map(|source| self.registry.loaded_mod(&source.mod_id))
+            .any(|m| m.fs.is_file(&format!("{dir}/{rel}")));
+
+        if !found {
+            self.error(
+                entry,
+                format!("{what} '{dir}/{}' does not exist", path.display()),
+            );
+        }
+    }
+}
+
+/// Ids of the nodes a dialogue can reach from its start node.
+fn dialogue_reachable(dialogue: &DialogueDef) -> BTreeSet<&str> {
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![dialogue.start.as_str()];
+    while let Some(id) = stack.pop() {
+        let Some(node) = dialogue.node(id) else {
+            continue;
+        };
+        if seen.insert(id) {
+            stack.extend(node.choices.iter().filter_map(|c| c.next.as_deref()));
+        }
+    }
+    seen
+}
+
+/// A chain of table references leading from `start` back to itself, if any.
+fn loot_table_cycle<'a>(registry: &'a ContentRegistry, start: &'a str) -> Option<Vec<&'a str>> {
+    fn visit<'a>(
+        registry: &'a ContentRegistry,
+        start: &str,
+        current: &'a str,
+        path: &mut Vec<&'a str>,
+        seen: &mut BTreeSet<&'a str>,
+    ) -> bool {
+        let Some(table) = registry.loot_tables.get(current) else {
+            return false;
+        };
+        for next in table.entries.iter().filter_map(|e| e.table.as_deref()) {
+            if next == start {
+                path.push(next);
+                return true;
+            }
+            if seen.insert(next) {
+                path.push(next);
+                if visit(registry, start, next, path, seen) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+
+    let mut path = vec![start];
+    visit(registry, start, start, &mut path, &mut BTreeSet::new()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        loader::ModLoader,
+        testing::{manifest, write_mods},
+    };
+
+    const BASE_DATA: &str = r#"[
+        Item(id: "nail", name: "Nail", description: "", components: []),
+        Bench(id: "workbench", name: "Workbench", craft_speed_mult: 1, noise_mult: 1),
+        Recipe(
+            id: "nail_pile",
+            name: "Nail Pile",
+            bench_id: "workbench",
+            time_s: 1,
+            ingredients: [(item: "nail", count: 2)],
+            outputs: [(item: "nail", count: 1)],
+        ),
+    ]"#;
+
+    /// Errors from validating a mods directory holding `base` with
+    /// [`BASE_DATA`] plus `files`.
+    fn errors(name: &str, files: &[(&str, &str)]) -> Vec<Diagnostic> {
+        let base_manifest = manifest("base", &[]);
+        let mut all = vec![
+            ("base/mod.toml", base_manifest.as_str()),
+            ("base/data/base.ron", BASE_DATA),
+        ];
+        all.extend_from_slice(files);
+        let dir = write_mods(name, &all);
+
+        let registry = ModLoader::new(&dir).load().unwrap();
+        validate(&registry)
+            .into_iter()
+            .filter(|d| d.severity == Severity::Error)
+            .collect()
+    }
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.message.as_str()).collect()
+    }
+
+    #[test]
+    fn consistent_content_has_no_errors() {
+        assert_eq!(messages(&errors("validate-clean", &[])), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn unknown_bench_and_items_are_errors() {
+        let errors = errors(
+            "validate-unknown",
+            &[(
+                "base/data/broken.ron",
+                r#"Recipe(
+                    id: "ghost_pile",
+                    name: "",
+                    bench_id: "forge",
+                    time_s: 1,
+                    ingredients: [(item: "ghost", count: 1)],
+                    outputs: [(item: "nail", count: 1)],
+                )"#,
+            )],
+        );
+        let messages = messages(&errors);
+        assert!(
+            messages.contains(&"unknown bench 'base:forge'"),
+            "{messages:?}"
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("unknown item 'base:ghost'")),
+            "{messages:?}"
+        );
+        assert!(
+            errors
+                .iter()
+                .all(|d| d.def_id.as_deref() == Some("base:ghost_pile"))
+        );
+    }
+
+    #[test]
+    fn loot_entries_need_min_up_to_max_and_a_weight() {
+        let errors = errors(
+            "validate-loot",
+            &[(
+                "base/data/loot.ron",
+                r#"LootTable(id: "rat", rolls: 1, entries: [
+                    (item: "nail", min: 3, max: 1),
+                    (weight: 0, item: "nail"),
+                    (weight: 0, item: "nail", guaranteed: true),
+                ])"#,
+            )],
+        );
+        assert_eq!(
+            messages(&errors),
+            [
+                "entry for item 'base:nail' has min 3 greater than max 1",
+                "entry for item 'base:nail' has a weight of 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn scripts_must_exist() {
+        let errors = errors(
+            "validate-script",
+            &[(
+                "base/data/scripted.ron",
+                r#"Recipe(
+                    id: "scripted",
+                    name: "",
+                    bench_id: "workbench",
+                    time_s: 1,
+                    ingredients: [],
+                    script: "craft/scripted.rhai",
+                )"#,
+            )],
+        );
+        assert_eq!(
+            messages(&errors),
+            ["script 'scripts/craft/scripted.rhai' does not exist"]
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_mod_that_last_touched_the_definition() {
+        let extra_manifest = manifest("extra", &["base"]);
+        let errors = errors(
+            "validate-attribution",
+            &[
+                ("extra/mod.toml", &extra_manifest),
+                (
+                    "extra/data/patches.ron",
+                    r#"Patch(Recipe(id: "base:nail_pile", bench_id: "base:forge"))"#,
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "{:?}", messages(&errors));
+        assert_eq!(errors[0].mod_id, "extra");
+        assert_eq!(errors[0].file, Path::new("data/patches.ron"));
+        assert_eq!(errors[0].def_id.as_deref(), Some("base:nail_pile"));
+    }
+
+    #[test]
+    fn hostile_threshold_must_be_below_friendly() {
+        let faction = |id: &str, hostile: i32, friendly: i32| {
+            format!(
+                r#"Faction(id: "{id}", name: "", thresholds: (hostile: {hostile}, friendly: {friendly}))"#
+            )
+        };
+        let factions = format!(
+            "[{}, {}, {}]",
+            faction("fair", -1, 0),
+            faction("equal", 5, 5),
+            faction("inverted", 10, -10)
+        );
+        let errors = errors(
+            "validate-thresholds",
+            &[("base/data/factions.ron", &factions)],
+        );
+        assert_eq!(
+            messages(&errors),
+            [
+                "hostile threshold 5 must be below friendly threshold 5",
+                "hostile threshold 10 must be below friendly threshold -10",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_text_is_only_reported_for_other_locales() {
+        let base = manifest("base", &[]);
+        let dir = write_mods(
+            "validate-missing-text",
+            &[
+                ("base/mod.toml", &base),
+                ("base/data/base.ron", BASE_DATA),
+                ("base/lang/en.ron", r#"{ "item.nail.name": "Nail" }"#),
+                ("base/lang/fr.ron", r#"{ "item.nail.name": "Clou" }"#),
+            ],
+        );
+        let registry = ModLoader::new(&dir).load().unwrap();
+        let warnings: Vec<String> = validate(&registry)
+            .into_iter()
+            .filter(|d| d.severity == Severity::Warning && d.message.starts_with("no '"))
+            .map(|d| format!("{}: {}", d.def_id.unwrap_or_default(), d.message))
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                "base:nail: no 'fr' text for item.base:nail.description",
+                "base:workbench: no 'fr' text for bench.base:workbench.name",
+                "base:nail_pile: no 'fr' text for recipe.base:nail_pile.name",
+            ]
+        );
+    }
+
+    #[test]
+    fn scripts_may_come_from_the_patching_mod() {
+        let extra_manifest = manifest("extra", &["base"]);
+        let errors = errors(
+            "validate-patched-script",
+            &[
+                ("extra/mod.toml", &extra_manifest),
+                (
+                    "extra/data/patches.ron",
+                    r#"Patch(Recipe(id: "base:nail_pile", script: "nails.rhai"))"#,
+                ),
+                ("extra/scripts/nails.rhai", ""),
+            ],
+        );
+        assert_eq!(messages(&errors), [] as [&str; 0]);
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use anyhow::{Context, Result, bail};
+use walkdir::WalkDir;
+use zip::ZipArchive;
+
+use crate::{
+    hashing::canonical_path,
+    ignore::{IgnoreRules, MODIGNORE_FILE},
+    loader::MANIFEST_FILE,
+};
+
+pub const ZIP_EXTENSION: &str = "zip";
+
+/// Read-only view of a mod's files, whether it ships as a folder or a `.zip`.
+///
+/// Files are addressed by their canonical path relative to the mod root (see
+/// [`canonical_path`]) and files excluded by the mod's [`IgnoreRules`] are
+/// hidden, so both forms of the same mod list, load and hash identically.
+#[derive(Debug, Clone)]
+pub struct ModFs {
+    root: PathBuf,
+    ignore: IgnoreRules,
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Dir,
+    Zip(Arc<ZipBackend>),
+}
+
+#[derive(Debug)]
+struct ZipBackend {
+    archive: Mutex<ZipArchive<File>>,
+    /// Canonical path of every file to its index in the archive.
+    entries: BTreeMap<String, usize>,
+}
+
+impl ModFs {
+    /// Opens a mod folder or a `.zip` archive.
+    pub fn open(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            Self::open_dir(path)
+        } else if is_zip_path(path) {
+            Self::open_zip(path)
+        } else {
+            bail!("{} is neither a folder nor a .zip archive", path.display())
+        }
+    }
+
+    pub fn open_dir(root: &Path) -> Result<Self> {
+        let path = root.join(MODIGNORE_FILE);
+        let ignore = if path.is_file() {
+            let text =
+                fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+            IgnoreRules::parse(&text)
+        } else {
+            IgnoreRules::default()
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            ignore,
+            backend: Backend::Dir,
+        })
+    }
+
+    /// Opens a zipped mod. Archives holding a single top-level folder (as made
+    /// by zipping the mod folder itself) are read from inside that folder.
+    pub fn open_zip(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+        let mut archive =
+            ZipArchive::new(file).with_context(|| format!("reading {}", path.display()))?;
+
+        let mut entries = BTreeMap::new();
+        for index in 0..archive.len() {
+            let entry = archive
+                .by_index_raw(index)
+                .with_context(|| format!("reading {}", path.display()))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let Some(name) = entry.enclosed_name() else {
+                bail!(
+                    "{} holds '{}', which escapes the archive",
+                    path.display(),
+                    entry.name()
+                );
+            };
+            entries.insert(canonical_path(&name)?, index);
+        }
+
+        if let Some(prefix) = shared_top_folder(&entries) {
+            entries = entries
+                .into_iter()
+                .map(|(name, index)| (name[prefix.len()..].to_owned(), index))
+                .collect();
+        }
+
+        let mut fs = Self {
+            root: path.to_path_buf(),
+            ignore: IgnoreRules::default(),
+            backend: Backend::Zip(Arc::new(ZipBackend {
+                archive: Mutex::new(archive),
+                entries,
+            })),
+        };
+        if fs.is_file(MODIGNORE_FILE) {
+            fs.ignore = IgnoreRules::parse(&fs.read_to_string(MODIGNORE_FILE)?);
+        }
+        Ok(fs)
+    }
+
+    /// The mod folder, or the archive for zipped mods.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The mod folder, if the mod is not zipped. Only folders can be written to.
+    pub fn dir(&self) -> Option<&Path> {
+        match self.backend {
+            Backend::Dir => Some(&self.root),
+            Backend::Zip(_) => None,
+        }
+    }
+
+    /// Canonical path of every file that is not ignored, sorted.
+    pub fn files(&self) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        match &self.backend {
+            Backend::Dir => {
+                for entry in walk_mod_dir(&self.root, &self.ignore) {
+                    let entry = entry?;
+                    if entry.file_type().is_file() {
+                        let rel = entry
+                            .path()
+                            .strip_prefix(&self.root)
+                            .unwrap_or(entry.path());
+                        files.push(canonical_path(rel)?);
+                    }
+                }
+            }
+            Backend::Zip(zip) => {
+                files.extend(
+                    zip.entries
+                        .keys()
+                        .filter(|rel| !self.ignore.is_file_ignored(rel))
+                        .cloned(),
+                );
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    pub fn is_file(&self, rel: &str) -> bool {
+        if self.ignore.is_file_ignored(rel) {
+            return false;
+        }
+        match &self.backend {
+            Backend::Dir => self.root.join(rel).is_file(),
+            Backend::Zip(zip) => zip.entries.contains_key(rel),
+        }
+    }
+
+    pub fn read(&self, rel: &str) -> Result<Vec<u8>> {
+        let path = self.display_path(rel);
+        match &self.backend {
+            Backend::Dir => fs::read(&path).with_context(|| format!("reading {}", path.display())),
+            Backend::Zip(zip) => {
+                let index = *zip
+                    .entries
+                    .get(rel)
+                    .with_context(|| format!("reading {}: no such file", path.display()))?;
+                let mut archive = zip.archive.lock().unwrap_or_else(PoisonError::into_inner);
+                let mut entry = archive
+                    .by_index(index)
+                    .with_context(|| format!("reading {}", path.display()))?;
+
+                let mut bytes = Vec::with_capacity(entry.size() as usize);
+                entry
+                    .read_to_end(&mut bytes)
+                    .with_context(|| format!("reading {}", path.display()))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    pub fn read_to_string(&self, rel: &str) -> Result<String> {
+        let bytes = self.read(rel)?;
+        String::from_utf8(bytes)
+            .with_context(|| format!("reading {}", self.display_path(rel).display()))
+    }
+
+    /// A path naming `rel` in messages, e.g. `mods/foo.zip/data/items/a.ron`.
+    pub fn display_path(&self, rel: &str) -> PathBuf {
+        self.root.join(rel)
+    }
+}
+
+pub fn is_zip_path(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(ZIP_EXTENSION))
+}
+
+/// `name/` if the archive has no `mod.toml` at its root and every file is
+/// under the same top-level folder.
+fn shared_top_folder(entries: &BTreeMap<String, usize>) -> Option<String> {
+    if entries.contains_key(MANIFEST_FILE) {
+        return None;
+    }
+
+    let mut shared = None;
+    for name in entries.keys() {
+        let (top, _) = name.split_once('/')?;
+        match shared {
+            None => shared = Some(top),
+            Some(prev) if prev == top => {}
+            Some(_) => return None,
+        }
+    }
+    shared.map(|top| format!("{top}/"))
+}
+
+/// Walks a mod folder in a stable order, skipping ignored files and directories.
+fn walk_mod_dir<'a>(
+    root: &'a Path,
+    ignore: &'a IgnoreRules,
+) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> + 'a {
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |entry| {
+            let Ok(rel) = entry.path().strip_prefix(root) else {
+                return true;
+            };
+            if rel.as_os_str().is_empty() {
+                return true;
+            }
+            match canonical_path(rel) {
+                Ok(rel) => !ignore.is_ignored(&rel, entry.file_type().is_dir()),
+                Err(_) => true,
+            }
+        })
+}
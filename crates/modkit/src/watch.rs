@@ -0,0 +1,105 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use walkdir::WalkDir;
+
+use crate::{
+    lang::LANG_DIR,
+    loader::{DATA_DIR, MANIFEST_FILE},
+    validate::SCRIPTS_DIR,
+    vfs::ZIP_EXTENSION,
+};
+
+/// Modification time and size of every file under a mods directory that can
+/// change the loaded content, to notice edits by polling where no file
+/// watcher is available. Assets are left out, as nothing loads them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModsSnapshot {
+    files: BTreeMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl ModsSnapshot {
+    pub fn take(mods_dir: &Path) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        for entry in WalkDir::new(mods_dir) {
+            let entry = entry.with_context(|| format!("scanning {}", mods_dir.display()))?;
+            if !entry.file_type().is_file()
+                || !entry
+                    .path()
+                    .strip_prefix(mods_dir)
+                    .is_ok_and(affects_content)
+            {
+                continue;
+            }
+            let meta = entry
+                .metadata()
+                .with_context(|| format!("reading {}", entry.path().display()))?;
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.insert(entry.into_path(), (modified, meta.len()));
+        }
+        Ok(Self { files })
+    }
+
+    /// Files added, removed or modified since `earlier`, sorted.
+    pub fn changed_since<'a>(&'a self, earlier: &'a ModsSnapshot) -> Vec<&'a Path> {
+        let added_or_modified = self
+            .files
+            .iter()
+            .filter(|(path, stamp)| earlier.files.get(*path) != Some(stamp))
+            .map(|(path, _)| path.as_path());
+        let removed = earlier
+            .files
+            .keys()
+            .filter(|path| !self.files.contains_key(*path))
+            .map(PathBuf::as_path);
+
+        let mut changed: Vec<&Path> = added_or_modified.chain(removed).collect();
+        changed.sort_unstable();
+        changed
+    }
+}
+
+/// Whether the file at `rel`, relative to the mods directory, is a zipped
+/// mod, a mod's manifest, or under its data, lang or scripts directory.
+fn affects_content(rel: &Path) -> bool {
+    let parts: Vec<&str> = rel.iter().map(|part| part.to_str().unwrap_or("")).collect();
+    match parts.as_slice() {
+        [archive] => Path::new(archive)
+            .extension()
+            .is_some_and(|ext| ext == ZIP_EXTENSION),
+        [_, file] => *file == MANIFEST_FILE,
+        [_, dir, _, ..] => [DATA_DIR, LANG_DIR, SCRIPTS_DIR].contains(dir),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_content_files_count() {
+        for rel in [
+            "base/mod.toml",
+            "base/data/items/claws.ron",
+            "base/lang/en.ron",
+            "base/scripts/status/poison.rhai",
+            "extra.zip",
+        ] {
+            assert!(affects_content(Path::new(rel)), "{rel}");
+        }
+        for rel in [
+            "base/assets/claws.png",
+            "base/README.md",
+            "base/data",
+            "base/nested/mod.toml",
+            "notes.txt",
+        ] {
+            assert!(!affects_content(Path::new(rel)), "{rel}");
+        }
+    }
+}
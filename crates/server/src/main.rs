@@ -1,7 +1,12 @@
-use std::{net::UdpSocket, time::SystemTime};
-
-use anyhow::Result;
-use bevy::{log::LogPlugin, prelude::*, state::app::StatesPlugin};
+use std::{collections::BTreeSet, net::UdpSocket, path::PathBuf, time::SystemTime};
+
+use anyhow::{Result, bail};
+use bevy::{
+    log::LogPlugin,
+    prelude::*,
+    state::app::StatesPlugin,
+    tasks::{AsyncComputeTaskPool, Task, futures::check_ready},
+};
 use bevy_replicon::prelude::*;
 use bevy_replicon::shared::backend::connected_client::NetworkId;
 use bevy_replicon_renet::{
@@ -11,6 +16,13 @@ use bevy_replicon_renet::{
 };
 use clap::Parser;
 use core::{NetTransform, PROTOCOL_ID, Player, PlayerInputCommand, register_replication};
+use modkit::{
+    diff::{DefChange, DefDiff, diff_registries},
+    loader::ModLoader,
+    registry::{ContentRegistry, DefKind},
+    validate::{Diagnostic, Severity, has_errors, validate},
+    watch::ModsSnapshot,
+};
 
 // Tuned for snappy top-down movement on a 320x180 virtual resolution.
 // Adjust these without touching networking or input code.
@@ -20,10 +32,60 @@ const DASH_SPEED_MULTIPLIER: f32 = 3.0;
 const DASH_DURATION_SECS: f32 = 0.18;
 const DASH_COOLDOWN_SECS: f32 = 0.8;
 
+/// How often `--hot-reload` checks the mods directory for edits.
+const HOT_RELOAD_POLL_SECS: f32 = 1.0;
+
 #[derive(Parser, Debug, Clone, Resource)]
 struct Args {
     #[arg(long, default_value = "127.0.0.1:5000")]
     addr: String,
+
+    #[arg(long, default_value = "mods")]
+    mods_dir: PathBuf,
+
+    /// Dev only: reload mod data whenever a mod's manifest, data, lang or
+    /// scripts under --mods-dir change.
+    /// Clients keep the mod hashes they joined with, so they may mismatch.
+    #[arg(long)]
+    hot_reload: bool,
+
+    /// Dev only: spawn an enemy with this full definition id at the origin
+    /// (repeatable). With --hot-reload, edits to its definition apply to it.
+    #[arg(long = "spawn-enemy")]
+    spawn_enemies: Vec<String>,
+}
+
+/// Merged content of every loaded mod. Validated before the server starts.
+#[derive(Resource)]
+struct Content(ContentRegistry);
+
+/// Polls the mods directory for edits when `--hot-reload` is on.
+#[derive(Resource)]
+struct HotReload {
+    snapshot: ModsSnapshot,
+    timer: Timer,
+    /// Loads and validates the mods off the main thread while a reload runs.
+    pending: Option<Task<Result<(ContentRegistry, Vec<Diagnostic>)>>>,
+}
+
+/// Sent after `--hot-reload` swapped in new content, with every definition
+/// that changed.
+#[derive(Message, Debug, Clone)]
+struct ContentReloaded {
+    diffs: Vec<DefDiff>,
+}
+
+/// The definition an entity was spawned from, by kind and full id.
+#[derive(Component, Debug, Clone)]
+struct FromDefinition {
+    kind: DefKind,
+    id: String,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+struct Health {
+    current: f32,
+    max: f32,
 }
 
 #[derive(Component)]
@@ -56,8 +118,6 @@ fn main() -> Result<()> {
     let args = Args::parse();
 
     let mut app = App::new();
-    app.insert_resource(args);
-
     app.add_plugins((
         MinimalPlugins,
         LogPlugin::default(),
@@ -66,6 +126,19 @@ fn main() -> Result<()> {
         RepliconRenetPlugins,
     ));
 
+    // Loaded once logging is set up, so diagnostics go through the log.
+    let content = load_content(&args)?;
+    if args.hot_reload {
+        app.insert_resource(HotReload {
+            snapshot: ModsSnapshot::take(&args.mods_dir)?,
+            timer: Timer::from_seconds(HOT_RELOAD_POLL_SECS, TimerMode::Repeating),
+            pending: None,
+        });
+    }
+    app.insert_resource(args);
+    app.insert_resource(content);
+    app.add_message::<ContentReloaded>();
+
     register_replication(&mut app);
     // Inputs are latency-sensitive; unordered delivery is fine for this prototype.
     app.add_client_message::<PlayerInputCommand>(Channel::Unordered);
@@ -75,7 +148,7 @@ fn main() -> Result<()> {
         lock_direction: true,
     });
 
-    app.add_systems(Startup, init_server);
+    app.add_systems(Startup, (init_server, log_content, spawn_enemies));
     app.add_systems(
         Update,
         (
@@ -84,6 +157,13 @@ fn main() -> Result<()> {
             receive_player_inputs,
             apply_player_movement,
             log_server_state,
+            (
+                (start_hot_reload, finish_hot_reload)
+                    .chain()
+                    .run_if(resource_exists::<HotReload>),
+                reapply_definitions,
+            )
+                .chain(),
         ),
     );
 
@@ -91,6 +171,207 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn load_content(args: &Args) -> Result<Content> {
+    let registry = ModLoader::new(&args.mods_dir).load()?;
+
+    let diagnostics = validate(&registry);
+    log_diagnostics(&diagnostics);
+    if has_errors(&diagnostics) {
+        bail!("mods in {} failed validation", args.mods_dir.display());
+    }
+    for id in &args.spawn_enemies {
+        if !registry.enemies.contains(id) {
+            bail!("--spawn-enemy: no enemy '{id}' is defined");
+        }
+    }
+
+    Ok(Content(registry))
+}
+
+fn log_diagnostics(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        match diagnostic.severity {
+            Severity::Error => error!("{diagnostic}"),
+            Severity::Warning => warn!("{diagnostic}"),
+        }
+    }
+}
+
+fn log_content(content: Res<Content>) {
+    let registry = &content.0;
+    let mods: Vec<_> = registry
+        .mods
+        .iter()
+        .map(|m| format!("{}@{}", m.manifest.mod_id, m.manifest.version))
+        .collect();
+
+    info!(
+        "content loaded: mods=[{}] items={} benches={} recipes={} loot_tables={} tags={} \
+         statuses={} enemies={} noise_kinds={} factions={} dialogues={} quests={}",
+        mods.join(", "),
+        registry.items.len(),
+        registry.benches.len(),
+        registry.recipes.len(),
+        registry.loot_tables.len(),
+        registry.tags.len(),
+        registry.statuses.len(),
+        registry.enemies.len(),
+        registry.noise_kinds.len(),
+        registry.factions.len(),
+        registry.dialogues.len(),
+        registry.quests.len(),
+    );
+}
+
+fn spawn_enemies(mut commands: Commands, args: Res<Args>, content: Res<Content>) {
+    for id in &args.spawn_enemies {
+        let def = content.0.enemies.get(id).expect("checked in load_content");
+        let entity = commands
+            .spawn((
+                Replicated,
+                NetTransform { x: 0.0, y: 0.0 },
+                FromDefinition {
+                    kind: DefKind::Enemy,
+                    id: id.clone(),
+                },
+                Health {
+                    current: def.health,
+                    max: def.health,
+                },
+            ))
+            .id();
+        info!("enemy spawned: entity={entity:?} id={id}");
+    }
+}
+
+/// Starts reloading every mod when a content file under the mods directory
+/// changed (see [`ModsSnapshot`]). Mods are reloaded whole rather than file
+/// by file, since overrides and patches in later mods depend on them.
+fn start_hot_reload(time: Res<Time>, args: Res<Args>, mut hot_reload: ResMut<HotReload>) {
+    if !hot_reload.timer.tick(time.delta()).just_finished() || hot_reload.pending.is_some() {
+        return;
+    }
+
+    let snapshot = match ModsSnapshot::take(&args.mods_dir) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            warn!("hot reload: {err:#}");
+            return;
+        }
+    };
+    let changed: Vec<_> = snapshot
+        .changed_since(&hot_reload.snapshot)
+        .into_iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+    // Taken now, so a broken edit is reported once rather than on every poll.
+    hot_reload.snapshot = snapshot;
+    info!("hot reload: changed {}", changed.join(", "));
+
+    let mods_dir = args.mods_dir.clone();
+    hot_reload.pending = Some(AsyncComputeTaskPool::get().spawn(async move {
+        let registry = ModLoader::new(&mods_dir).load()?;
+        let diagnostics = validate(&registry);
+        Ok((registry, diagnostics))
+    }));
+}
+
+/// Swaps in the content of a finished reload if it validates.
+fn finish_hot_reload(
+    mut hot_reload: ResMut<HotReload>,
+    mut content: ResMut<Content>,
+    mut reloaded: MessageWriter<ContentReloaded>,
+) {
+    let Some(result) = hot_reload.pending.as_mut().and_then(check_ready) else {
+        return;
+    };
+    hot_reload.pending = None;
+
+    let (registry, diagnostics) = match result {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            error!("hot reload rejected, keeping current content: {err:#}");
+            return;
+        }
+    };
+
+    log_diagnostics(&diagnostics);
+    if has_errors(&diagnostics) {
+        error!("hot reload rejected, keeping current content: mods failed validation");
+        return;
+    }
+
+    let diffs = diff_registries(&content.0, &registry);
+    content.0 = registry;
+    if diffs.is_empty() {
+        info!("hot reload: no definitions changed");
+        return;
+    }
+    for diff in &diffs {
+        info!("hot reload: {diff}");
+    }
+    reloaded.write(ContentReloaded { diffs });
+}
+
+/// Applies reloaded definitions to the entities spawned from them. An enemy
+/// keeps its share of health when its maximum changes. Entities whose
+/// definition was removed keep what they have.
+fn reapply_definitions(
+    mut reloaded: MessageReader<ContentReloaded>,
+    content: Res<Content>,
+    mut entities: Query<(Entity, &FromDefinition, Option<&mut Health>)>,
+) {
+    let mut changed = BTreeSet::new();
+    let mut removed = BTreeSet::new();
+    for message in reloaded.read() {
+        for diff in &message.diffs {
+            let key = (diff.kind, diff.id.as_str());
+            match diff.change {
+                DefChange::Changed(_) => changed.insert(key),
+                DefChange::Removed => removed.insert(key),
+                DefChange::Added => false,
+            };
+        }
+    }
+    if changed.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    for (entity, source, health) in &mut entities {
+        let key = (source.kind, source.id.as_str());
+        if removed.contains(&key) {
+            warn!(
+                "hot reload: {entity:?} uses {} '{}', which no longer exists",
+                source.kind, source.id
+            );
+            continue;
+        }
+        if !changed.contains(&key) {
+            continue;
+        }
+
+        if source.kind == DefKind::Enemy
+            && let Some(def) = content.0.enemies.get(&source.id)
+            && let Some(mut health) = health
+        {
+            let share = if health.max > 0.0 {
+                health.current / health.max
+            } else {
+                1.0
+            };
+            health.max = def.health;
+            health.current = def.health * share;
+            info!(
+                "hot reload: {entity:?} now uses enemy '{}': health {}/{}",
+                source.id, health.current, health.max
+            );
+        }
+    }
+}
+
 fn init_server(mut commands: Commands, args: Res<Args>, channels: Res<RepliconChannels>) {
     let server_addr = args.addr.parse().expect("invalid --addr");
     let socket = UdpSocket::bind(server_addr).expect("failed to bind UDP socket");